@@ -0,0 +1,250 @@
+use serde::Serialize;
+use std::{
+    fmt,
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use crate::cli::{OutputFormat, Part};
+use crate::errors::{AppError, AppResult};
+use crate::registry::Day;
+
+pub const DEFAULT_RUNS: usize = 10;
+
+pub fn time<T, F>(f: F) -> (T, Duration)
+where
+    F: FnOnce() -> T,
+{
+    let start = Instant::now();
+    let value = f();
+
+    (value, start.elapsed())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Parse,
+    Solve(Part),
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stage::Parse => write!(f, "parse"),
+            Stage::Solve(part) => write!(f, "part{}", part),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &mut [Duration]) -> Option<Self> {
+        samples.sort_unstable();
+
+        Some(Self {
+            runs: samples.len(),
+            min: *samples.first()?,
+            median: samples[samples.len() / 2],
+            max: *samples.last()?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Measurement {
+    pub day: u8,
+    pub stage: Stage,
+    pub result: AppResult<Stats>,
+}
+
+// Every run parses the input again before solving both parts, so the parse
+// time is recorded on its own instead of being folded into the solve time.
+pub fn bench_day(day: &Day, input: &str, runs: usize) -> Vec<Measurement> {
+    let stages = [
+        Stage::Parse,
+        Stage::Solve(Part::One),
+        Stage::Solve(Part::Two),
+    ];
+    let mut samples = stages.iter().map(|_| vec![]).collect::<Vec<_>>();
+    let mut errors = stages.iter().map(|_| None).collect::<Vec<_>>();
+
+    for _ in 0..runs {
+        let (parsed, elapsed) = time(|| day.solution.parse(input));
+        let parsed = match parsed {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                errors[0] = Some(err);
+                break;
+            }
+        };
+        samples[0].push(elapsed);
+
+        for (index, stage) in stages.iter().enumerate().skip(1) {
+            let part = match stage {
+                Stage::Solve(part) => *part,
+                Stage::Parse => continue,
+            };
+
+            if errors[index].is_some() {
+                continue;
+            }
+
+            match time(|| parsed.solve(part)) {
+                (Ok(_), elapsed) => samples[index].push(elapsed),
+                (Err(err), _) => errors[index] = Some(err),
+            }
+        }
+    }
+
+    stages
+        .iter()
+        .zip(samples.iter_mut().zip(errors))
+        .map(|(&stage, (samples, error))| Measurement {
+            day: day.number,
+            stage,
+            result: match error {
+                Some(err) => Err(err),
+                None => Stats::from_samples(samples)
+                    .ok_or_else(|| AppError::invalid_input("Failed to parse input")),
+            },
+        })
+        .collect()
+}
+
+fn write_text<W: Write>(mut writer: W, measurements: &[Measurement]) -> AppResult<()> {
+    writeln!(
+        writer,
+        "{:<5} {:<7} {:>12} {:>12} {:>12}",
+        "Day", "Stage", "Min", "Median", "Max"
+    )?;
+
+    for Measurement { day, stage, result } in measurements {
+        match result {
+            Ok(stats) => writeln!(
+                writer,
+                "{:<5} {:<7} {:>12} {:>12} {:>12}",
+                day,
+                stage.to_string(),
+                format!("{:.2?}", stats.min),
+                format!("{:.2?}", stats.median),
+                format!("{:.2?}", stats.max)
+            )?,
+            Err(err) => writeln!(writer, "{:<5} {:<7} {}", day, stage.to_string(), err)?,
+        }
+    }
+
+    Ok(())
+}
+
+fn write_tsv<W: Write>(mut writer: W, measurements: &[Measurement]) -> AppResult<()> {
+    writeln!(writer, "day\tstage\truns\tmin_ns\tmedian_ns\tmax_ns\terror")?;
+
+    for Measurement { day, stage, result } in measurements {
+        match result {
+            Ok(stats) => writeln!(
+                writer,
+                "{}\t{}\t{}\t{}\t{}\t{}\t",
+                day,
+                stage,
+                stats.runs,
+                stats.min.as_nanos(),
+                stats.median.as_nanos(),
+                stats.max.as_nanos()
+            )?,
+            Err(err) => writeln!(writer, "{}\t{}\t0\t\t\t\t{}", day, stage, err)?,
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct JsonMeasurement {
+    day: u8,
+    stage: String,
+    runs: usize,
+    min_ns: Option<u64>,
+    median_ns: Option<u64>,
+    max_ns: Option<u64>,
+    error: Option<String>,
+}
+
+fn write_json<W: Write>(mut writer: W, measurements: &[Measurement]) -> AppResult<()> {
+    for Measurement { day, stage, result } in measurements {
+        let record = match result {
+            Ok(stats) => JsonMeasurement {
+                day: *day,
+                stage: stage.to_string(),
+                runs: stats.runs,
+                min_ns: Some(stats.min.as_nanos() as u64),
+                median_ns: Some(stats.median.as_nanos() as u64),
+                max_ns: Some(stats.max.as_nanos() as u64),
+                error: None,
+            },
+            Err(err) => JsonMeasurement {
+                day: *day,
+                stage: stage.to_string(),
+                runs: 0,
+                min_ns: None,
+                median_ns: None,
+                max_ns: None,
+                error: Some(err.to_string()),
+            },
+        };
+
+        serde_json::to_writer(&mut writer, &record).map_err(io::Error::from)?;
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+pub fn write_measurements<W: Write>(
+    writer: W,
+    measurements: &[Measurement],
+    format: OutputFormat,
+) -> AppResult<()> {
+    match format {
+        OutputFormat::Text => write_text(writer, measurements),
+        OutputFormat::Tsv => write_tsv(writer, measurements),
+        OutputFormat::Json => write_json(writer, measurements),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_from_samples() {
+        let mut samples = [5, 1, 4, 2, 3].map(Duration::from_millis);
+        let stats = Stats::from_samples(&mut samples).unwrap();
+
+        assert_eq!(stats.runs, 5);
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_millis(3));
+        assert_eq!(stats.max, Duration::from_millis(5));
+    }
+
+    #[test]
+    fn median_of_even_samples() {
+        let mut samples = [4, 1, 3, 2].map(Duration::from_millis);
+
+        assert_eq!(
+            Stats::from_samples(&mut samples).unwrap().median,
+            Duration::from_millis(3)
+        );
+    }
+
+    #[test]
+    fn no_samples() {
+        assert!(Stats::from_samples(&mut []).is_none());
+    }
+}
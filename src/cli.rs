@@ -0,0 +1,765 @@
+use serde::{Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+use crate::bench::DEFAULT_RUNS;
+use crate::errors::{AppError, AppResult};
+use crate::records::{DuplicateKeys, UnknownKeys};
+use crate::{d01, d02, d03, d04};
+
+pub const USAGE: &str = "\
+Usage: advent-of-code-2020 <command> [options]
+
+Commands:
+    run <days>      Run the given day(s), e.g. `run 3` or `run 1..=7`
+    run --all       Run every implemented day
+    bench [days]    Time parsing and solving of the given day(s), or every
+                    day
+    verify [days]   Check the answers of the given day(s), or every day,
+                    against the answers file
+    k-sum           List every combination of expense report entries (day
+                    1) that sums to the target
+    passwords       Count the passwords (day 2) valid under a policy
+    subset-sum      Find the subset of expense report entries of any size
+                    that sums to the target with the fewest, or most,
+                    entries
+    slopes          Find the toboggan slopes (day 3) hitting the fewest and
+                    the most trees. Slopes visiting identical cells are
+                    listed together, but a multiple of a slope like (2, 2)
+                    of (1, 1) only visits some of its cells, so it's listed
+                    on its own
+    render          Draw the toboggan paths (day 3) over the map
+    passports       Count the passports (day 4) valid under a schema
+    help            Print this message
+
+Options:
+    --part <1|2>        Only run the given part of each day
+    --input <path|->    Read the puzzle input from a file, or stdin when given
+                        `-`, instead of the day's file under `data/`, or
+                        under the AOC_DATA_DIR environment variable when set.
+                        Falls back to the AOC_INPUT environment variable
+    --answers <path>    Answers file used by `verify`, defaults to the
+                        AOC_ANSWERS environment variable or `answers.toml`
+    --runs <count>      Number of runs per day for `bench`, defaults to 10
+    --format <format>   Output format of `run` and `bench`, either `text`,
+                        `tsv` or `json`
+    --k <count>         Number of entries summed by `k-sum`, defaults to 2
+    --target <sum>      Sum searched for by `k-sum`, defaults to 2020
+    --limit <count>     Stop `k-sum` after the given number of combinations
+    --fewest, --most    Whether `subset-sum` looks for the fewest (default) or
+                        the most entries
+    --policy <policy>   Policy used by `passwords`, defaults to `sled`. Either
+                        `sled`, `toboggan`, `min-length=<n>`,
+                        `classes=<lower|upper|digit|symbol>[+...]`,
+                        `forbid=<text>[+...]`, or `all(...)`, `any(...)` and
+                        `none(...)` of other policies separated by `,`. The
+                        text forbidden can't be empty, nor contain `(`, `)`,
+                        `,` or `+`
+    --strict, --skip-invalid
+                        Whether `passwords` fails on the first invalid line
+                        (default) or skips invalid lines, reporting them on
+                        stderr
+    --audit <format>    Have `passwords` explain the verdict on every password,
+                        as `text` or `csv`
+    --progress          Report how many passwords `passwords` has checked on
+                        stderr
+    --max-run <n>       Largest run searched by `slopes`, defaults to 7
+    --max-rise <n>      Largest rise searched by `slopes`, defaults to 2
+    --slope <run,rise>  Slope drawn by `render`, can be given more than once,
+                        defaults to `3,1`
+    --ppm               Have `render` write a PPM image, with a color per
+                        slope, instead of text
+    --scale <n>         Pixels per map square in `render` images, defaults
+                        to 4
+    --schema <path>     TOML, or JSON, schema of the passport fields used by
+                        `passports`, defaults to the puzzle's rules. See
+                        `passport-schema.toml`
+    --explain           Have `passports` list every rule each invalid passport
+                        breaks
+    --strict, --lenient Whether `passports` explains and tallies every rule
+                        broken (default) or only missing fields. Both the
+                        count of passports with every required field and the
+                        count of valid passports are printed either way.
+                        `--required-only` is the same as `--lenient`
+    --duplicates <policy>
+                        Which value `passports` keeps for a key given twice in
+                        a record, `first`, `last` (default) or `error`
+    --unknown-keys <policy>
+                        Whether `passports` keeps (default), drops or errors on
+                        keys outside of the schema, `keep`, `drop` or `error`
+    --normalize         Have `passports` write the records back out, one per
+                        line with sorted keys, instead of checking them
+    --export <format>   Have `passports` write every passport with its typed
+                        fields, verdict and failing rules, as `csv` or `json`
+                        lines, instead of the counts
+    --summary           Have `passports` tally the passports rejected by each
+                        rule, most first";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Serialize for Part {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(match self {
+            Part::One => 1,
+            Part::Two => 2,
+        })
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartSelection {
+    Both,
+    Only(Part),
+}
+
+impl PartSelection {
+    pub fn includes(&self, part: Part) -> bool {
+        match self {
+            PartSelection::Both => true,
+            PartSelection::Only(selected) => *selected == part,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Tsv,
+    Json,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DaySelection {
+    All,
+    Days(Vec<u8>),
+}
+
+/// How `passports` reads the batch file and what it reports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassportOptions {
+    pub schema: Option<String>,
+    pub explain: bool,
+    pub summary: bool,
+    pub validation: d04::Validation,
+    pub duplicates: DuplicateKeys,
+    pub unknown_keys: UnknownKeys,
+    pub normalize: bool,
+    pub export: Option<d04::ExportFormat>,
+}
+
+impl Default for PassportOptions {
+    fn default() -> Self {
+        PassportOptions {
+            schema: None,
+            explain: false,
+            summary: false,
+            validation: d04::Validation::EveryRule,
+            duplicates: DuplicateKeys::Last,
+            unknown_keys: UnknownKeys::Keep,
+            normalize: false,
+            export: None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Run {
+        days: DaySelection,
+        parts: PartSelection,
+        input: Option<String>,
+        format: OutputFormat,
+    },
+    Verify {
+        days: DaySelection,
+        answers: Option<String>,
+    },
+    Bench {
+        days: DaySelection,
+        runs: usize,
+        format: OutputFormat,
+    },
+    KSum {
+        k: usize,
+        target: u32,
+        limit: Option<usize>,
+        input: Option<String>,
+    },
+    SubsetSum {
+        target: u32,
+        objective: d01::Objective,
+        input: Option<String>,
+    },
+    Passwords {
+        policy: String,
+        mode: d02::ParseMode,
+        audit: Option<d02::AuditFormat>,
+        progress: bool,
+        input: Option<String>,
+    },
+    Slopes {
+        bound: d03::Slope,
+        input: Option<String>,
+    },
+    Render {
+        slopes: Vec<d03::Slope>,
+        ppm: bool,
+        scale: usize,
+        input: Option<String>,
+    },
+    Passports {
+        options: PassportOptions,
+        input: Option<String>,
+    },
+    Help,
+}
+
+fn parse_day(value: &str) -> AppResult<u8> {
+    value
+        .parse::<u8>()
+        .map_err(|_| AppError::invalid_input(&format!("Invalid day \"{}\"", value)))
+}
+
+fn parse_days(value: &str) -> AppResult<Vec<u8>> {
+    let days = match value.find("..") {
+        Some(index) => {
+            let start = parse_day(&value[..index])?;
+            let end = &value[(index + 2)..];
+            match end.strip_prefix('=') {
+                Some(end) => (start..=parse_day(end)?).collect::<Vec<_>>(),
+                None => (start..parse_day(end)?).collect(),
+            }
+        }
+        None => vec![parse_day(value)?],
+    };
+
+    if days.is_empty() {
+        return Err(AppError::invalid_input(&format!(
+            "Empty day range \"{}\"",
+            value
+        )));
+    }
+
+    Ok(days)
+}
+
+fn parse_part(value: Option<String>) -> AppResult<Part> {
+    match value.as_deref() {
+        Some("1") => Ok(Part::One),
+        Some("2") => Ok(Part::Two),
+        Some(value) => Err(AppError::invalid_input(&format!(
+            "Invalid part \"{}\"",
+            value
+        ))),
+        None => Err(AppError::invalid_input("Missing value for --part")),
+    }
+}
+
+fn parse_format(value: Option<String>) -> AppResult<OutputFormat> {
+    match parse_value("--format", value)?.as_str() {
+        "text" => Ok(OutputFormat::Text),
+        "tsv" => Ok(OutputFormat::Tsv),
+        "json" => Ok(OutputFormat::Json),
+        value => Err(AppError::invalid_input(&format!(
+            "Unknown format \"{}\"",
+            value
+        ))),
+    }
+}
+
+fn parse_audit_format(value: Option<String>) -> AppResult<d02::AuditFormat> {
+    match parse_value("--audit", value)?.as_str() {
+        "text" => Ok(d02::AuditFormat::Text),
+        "csv" => Ok(d02::AuditFormat::Csv),
+        value => Err(AppError::invalid_input(&format!(
+            "Unknown audit format \"{}\"",
+            value
+        ))),
+    }
+}
+
+fn parse_duplicates(value: Option<String>) -> AppResult<DuplicateKeys> {
+    match parse_value("--duplicates", value)?.as_str() {
+        "first" => Ok(DuplicateKeys::First),
+        "last" => Ok(DuplicateKeys::Last),
+        "error" => Ok(DuplicateKeys::Error),
+        value => Err(AppError::invalid_input(&format!(
+            "Unknown duplicate key policy \"{}\"",
+            value
+        ))),
+    }
+}
+
+fn parse_unknown_keys(value: Option<String>) -> AppResult<UnknownKeys> {
+    match parse_value("--unknown-keys", value)?.as_str() {
+        "keep" => Ok(UnknownKeys::Keep),
+        "drop" => Ok(UnknownKeys::Drop),
+        "error" => Ok(UnknownKeys::Error),
+        value => Err(AppError::invalid_input(&format!(
+            "Unknown unknown key policy \"{}\"",
+            value
+        ))),
+    }
+}
+
+fn parse_export_format(value: Option<String>) -> AppResult<d04::ExportFormat> {
+    match parse_value("--export", value)?.as_str() {
+        "csv" => Ok(d04::ExportFormat::Csv),
+        "json" => Ok(d04::ExportFormat::JsonLines),
+        value => Err(AppError::invalid_input(&format!(
+            "Unknown export format \"{}\"",
+            value
+        ))),
+    }
+}
+
+fn parse_runs(value: Option<String>) -> AppResult<usize> {
+    match parse_value("--runs", value)?.parse::<usize>() {
+        Ok(runs) if runs > 0 => Ok(runs),
+        _ => Err(AppError::invalid_input("--runs must be a positive number")),
+    }
+}
+
+fn parse_value(option: &str, value: Option<String>) -> AppResult<String> {
+    value.ok_or_else(|| AppError::invalid_input(&format!("Missing value for {}", option)))
+}
+
+fn parse_number<T: FromStr>(option: &str, value: Option<String>) -> AppResult<T> {
+    let value = parse_value(option, value)?;
+
+    value.parse::<T>().map_err(|_| {
+        AppError::invalid_input(&format!("Invalid number \"{}\" for {}", value, option))
+    })
+}
+
+fn parse_run<I>(mut args: I) -> AppResult<Command>
+where
+    I: Iterator<Item = String>,
+{
+    let mut days = None;
+    let mut parts = PartSelection::Both;
+    let mut input = None;
+    let mut format = OutputFormat::Text;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--all" if days.is_none() => days = Some(DaySelection::All),
+            "--part" => parts = PartSelection::Only(parse_part(args.next())?),
+            "--input" => input = Some(parse_value("--input", args.next())?),
+            "--format" => format = parse_format(args.next())?,
+            value if value.starts_with("--") => {
+                return Err(AppError::invalid_input(&format!(
+                    "Unknown option \"{}\"",
+                    value
+                )))
+            }
+            value if days.is_none() => days = Some(DaySelection::Days(parse_days(value)?)),
+            value => {
+                return Err(AppError::invalid_input(&format!(
+                    "Unexpected argument \"{}\"",
+                    value
+                )))
+            }
+        }
+    }
+
+    match days {
+        Some(days) => Ok(Command::Run {
+            days,
+            parts,
+            input,
+            format,
+        }),
+        None => Err(AppError::invalid_input(
+            "Must provide a day, a range of days or --all",
+        )),
+    }
+}
+
+fn parse_verify<I>(mut args: I) -> AppResult<Command>
+where
+    I: Iterator<Item = String>,
+{
+    let mut days = None;
+    let mut answers = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--all" if days.is_none() => days = Some(DaySelection::All),
+            "--answers" => answers = Some(parse_value("--answers", args.next())?),
+            value if value.starts_with("--") => {
+                return Err(AppError::invalid_input(&format!(
+                    "Unknown option \"{}\"",
+                    value
+                )))
+            }
+            value if days.is_none() => days = Some(DaySelection::Days(parse_days(value)?)),
+            value => {
+                return Err(AppError::invalid_input(&format!(
+                    "Unexpected argument \"{}\"",
+                    value
+                )))
+            }
+        }
+    }
+
+    Ok(Command::Verify {
+        days: days.unwrap_or(DaySelection::All),
+        answers,
+    })
+}
+
+fn parse_bench<I>(mut args: I) -> AppResult<Command>
+where
+    I: Iterator<Item = String>,
+{
+    let mut days = None;
+    let mut runs = DEFAULT_RUNS;
+    let mut format = OutputFormat::Text;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--all" if days.is_none() => days = Some(DaySelection::All),
+            "--runs" => runs = parse_runs(args.next())?,
+            "--format" => format = parse_format(args.next())?,
+            value if value.starts_with("--") => {
+                return Err(AppError::invalid_input(&format!(
+                    "Unknown option \"{}\"",
+                    value
+                )))
+            }
+            value if days.is_none() => days = Some(DaySelection::Days(parse_days(value)?)),
+            value => {
+                return Err(AppError::invalid_input(&format!(
+                    "Unexpected argument \"{}\"",
+                    value
+                )))
+            }
+        }
+    }
+
+    Ok(Command::Bench {
+        days: days.unwrap_or(DaySelection::All),
+        runs,
+        format,
+    })
+}
+
+fn parse_k_sum<I>(mut args: I) -> AppResult<Command>
+where
+    I: Iterator<Item = String>,
+{
+    let mut k = 2;
+    let mut target = d01::TARGET;
+    let mut limit = None;
+    let mut input = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--k" => k = parse_number("--k", args.next())?,
+            "--target" => target = parse_number("--target", args.next())?,
+            "--limit" => limit = Some(parse_number("--limit", args.next())?),
+            "--input" => input = Some(parse_value("--input", args.next())?),
+            value => {
+                return Err(AppError::invalid_input(&format!(
+                    "Unexpected argument \"{}\"",
+                    value
+                )))
+            }
+        }
+    }
+
+    Ok(Command::KSum {
+        k,
+        target,
+        limit,
+        input,
+    })
+}
+
+fn parse_subset_sum<I>(mut args: I) -> AppResult<Command>
+where
+    I: Iterator<Item = String>,
+{
+    let mut target = d01::TARGET;
+    let mut objective = d01::Objective::FewestItems;
+    let mut input = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--target" => target = parse_number("--target", args.next())?,
+            "--fewest" => objective = d01::Objective::FewestItems,
+            "--most" => objective = d01::Objective::MostItems,
+            "--input" => input = Some(parse_value("--input", args.next())?),
+            value => {
+                return Err(AppError::invalid_input(&format!(
+                    "Unexpected argument \"{}\"",
+                    value
+                )))
+            }
+        }
+    }
+
+    Ok(Command::SubsetSum {
+        target,
+        objective,
+        input,
+    })
+}
+
+fn parse_passwords<I>(mut args: I) -> AppResult<Command>
+where
+    I: Iterator<Item = String>,
+{
+    let mut policy = "sled".to_string();
+    let mut mode = d02::ParseMode::Strict;
+    let mut audit = None;
+    let mut progress = false;
+    let mut input = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--policy" => policy = parse_value("--policy", args.next())?,
+            "--strict" => mode = d02::ParseMode::Strict,
+            "--skip-invalid" => mode = d02::ParseMode::SkipInvalid,
+            "--audit" => audit = Some(parse_audit_format(args.next())?),
+            "--progress" => progress = true,
+            "--input" => input = Some(parse_value("--input", args.next())?),
+            value => {
+                return Err(AppError::invalid_input(&format!(
+                    "Unexpected argument \"{}\"",
+                    value
+                )))
+            }
+        }
+    }
+
+    Ok(Command::Passwords {
+        policy,
+        mode,
+        audit,
+        progress,
+        input,
+    })
+}
+
+fn parse_slopes<I>(mut args: I) -> AppResult<Command>
+where
+    I: Iterator<Item = String>,
+{
+    let mut bound = d03::Slope { run: 7, rise: 2 };
+    let mut input = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--max-run" => bound.run = parse_number("--max-run", args.next())?,
+            "--max-rise" => bound.rise = parse_number("--max-rise", args.next())?,
+            "--input" => input = Some(parse_value("--input", args.next())?),
+            value => {
+                return Err(AppError::invalid_input(&format!(
+                    "Unexpected argument \"{}\"",
+                    value
+                )))
+            }
+        }
+    }
+
+    Ok(Command::Slopes { bound, input })
+}
+
+fn parse_slope(value: Option<String>) -> AppResult<d03::Slope> {
+    let value = parse_value("--slope", value)?;
+    let invalid = || AppError::invalid_input(&format!("Invalid slope \"{}\"", value));
+
+    let (run, rise) = value.split_once(',').ok_or_else(invalid)?;
+
+    Ok(d03::Slope {
+        run: run.trim().parse().map_err(|_| invalid())?,
+        rise: rise.trim().parse().map_err(|_| invalid())?,
+    })
+}
+
+fn parse_render<I>(mut args: I) -> AppResult<Command>
+where
+    I: Iterator<Item = String>,
+{
+    let mut slopes = vec![];
+    let mut ppm = false;
+    let mut scale = 4;
+    let mut input = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--slope" => slopes.push(parse_slope(args.next())?),
+            "--ppm" => ppm = true,
+            "--scale" => scale = parse_number("--scale", args.next())?,
+            "--input" => input = Some(parse_value("--input", args.next())?),
+            value => {
+                return Err(AppError::invalid_input(&format!(
+                    "Unexpected argument \"{}\"",
+                    value
+                )))
+            }
+        }
+    }
+
+    if slopes.is_empty() {
+        slopes.push(d03::Slope { run: 3, rise: 1 });
+    }
+
+    Ok(Command::Render {
+        slopes,
+        ppm,
+        scale,
+        input,
+    })
+}
+
+fn parse_passports<I>(mut args: I) -> AppResult<Command>
+where
+    I: Iterator<Item = String>,
+{
+    let mut options = PassportOptions::default();
+    let mut input = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--schema" => options.schema = Some(parse_value("--schema", args.next())?),
+            "--explain" => options.explain = true,
+            "--summary" => options.summary = true,
+            "--strict" => options.validation = d04::Validation::EveryRule,
+            "--lenient" | "--required-only" => options.validation = d04::Validation::RequiredFields,
+            "--duplicates" => options.duplicates = parse_duplicates(args.next())?,
+            "--unknown-keys" => options.unknown_keys = parse_unknown_keys(args.next())?,
+            "--normalize" => options.normalize = true,
+            "--export" => options.export = Some(parse_export_format(args.next())?),
+            "--input" => input = Some(parse_value("--input", args.next())?),
+            value => {
+                return Err(AppError::invalid_input(&format!(
+                    "Unexpected argument \"{}\"",
+                    value
+                )))
+            }
+        }
+    }
+
+    Ok(Command::Passports { options, input })
+}
+
+pub fn parse_args<I>(mut args: I) -> AppResult<Command>
+where
+    I: Iterator<Item = String>,
+{
+    match args.next().as_deref() {
+        Some("run") => parse_run(args),
+        Some("verify") => parse_verify(args),
+        Some("bench") => parse_bench(args),
+        Some("k-sum") => parse_k_sum(args),
+        Some("subset-sum") => parse_subset_sum(args),
+        Some("passwords") => parse_passwords(args),
+        Some("slopes") => parse_slopes(args),
+        Some("render") => parse_render(args),
+        Some("passports") => parse_passports(args),
+        Some("help") | Some("--help") | Some("-h") => Ok(Command::Help),
+        Some(command) => Err(AppError::invalid_input(&format!(
+            "Unknown command \"{}\"\n\n{}",
+            command, USAGE
+        ))),
+        None => Err(AppError::invalid_input(USAGE)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> impl Iterator<Item = String> + '_ {
+        line.split_whitespace().map(String::from)
+    }
+
+    #[test]
+    fn day_ranges() {
+        assert_eq!(parse_days("3").unwrap(), vec![3]);
+        assert_eq!(parse_days("1..4").unwrap(), vec![1, 2, 3]);
+        assert_eq!(parse_days("1..=4").unwrap(), vec![1, 2, 3, 4]);
+        assert_eq!(parse_days("4..=4").unwrap(), vec![4]);
+    }
+
+    #[test]
+    fn empty_and_reversed_ranges() {
+        for value in ["0..0", "4..4", "5..2", "5..=2"].iter() {
+            match parse_days(value) {
+                Err(AppError::InvalidInput(message)) => {
+                    assert!(message.starts_with("Empty day range"), "{}", message)
+                }
+                result => panic!("Expected an empty range for {}, found {:?}", value, result),
+            }
+        }
+    }
+
+    #[test]
+    fn non_numeric_days() {
+        for value in ["x", "1..x", "x..=3", "-1", "..3", "1..", "256"].iter() {
+            match parse_days(value) {
+                Err(AppError::InvalidInput(message)) => {
+                    assert!(message.starts_with("Invalid day"), "{}", message)
+                }
+                result => panic!("Expected an invalid day for {}, found {:?}", value, result),
+            }
+        }
+    }
+
+    #[test]
+    fn run_command() {
+        assert_eq!(
+            parse_args(args("run 1..=3 --part 2 --format json")).unwrap(),
+            Command::Run {
+                days: DaySelection::Days(vec![1, 2, 3]),
+                parts: PartSelection::Only(Part::Two),
+                input: None,
+                format: OutputFormat::Json,
+            }
+        );
+        assert!(parse_args(args("run 0..0")).is_err());
+        assert!(parse_args(args("run")).is_err());
+        assert!(parse_args(args("run 1 2")).is_err());
+    }
+
+    #[test]
+    fn passports_command() {
+        assert_eq!(
+            parse_args(args("passports --lenient --explain")).unwrap(),
+            Command::Passports {
+                options: PassportOptions {
+                    explain: true,
+                    validation: d04::Validation::RequiredFields,
+                    ..PassportOptions::default()
+                },
+                input: None,
+            }
+        );
+        assert_eq!(
+            parse_args(args("passports --lenient --strict")).unwrap(),
+            Command::Passports {
+                options: PassportOptions::default(),
+                input: None,
+            }
+        );
+        assert_eq!(
+            parse_args(args("passports --required-only")).unwrap(),
+            parse_args(args("passports --lenient")).unwrap()
+        );
+    }
+}
@@ -1,67 +1,744 @@
-use crate::errors::{AppError, AppResult};
-use std::{fs::File, io::BufRead, io::BufReader};
+use crate::errors::{AppError, AppResult, ParseError};
+use crate::solution::Solution;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    io::{self, BufRead, Write},
+};
 
-fn read_expense_report(path: &str) -> AppResult<Vec<u32>> {
-    BufReader::new(File::open(path)?)
+/// Reads one expense per line.
+pub fn read_expense_report<R: BufRead>(reader: R) -> AppResult<Vec<u32>> {
+    reader
         .lines()
-        .map(|line| line?.parse::<u32>().map_err(AppError::from))
+        .enumerate()
+        .map(|(index, line)| {
+            let line = line?;
+            line.parse::<u32>().map_err(|err| {
+                ParseError::new(index + 1, 1, &format!("Invalid expense \"{}\"", line))
+                    .with_source(err)
+                    .into()
+            })
+        })
         .collect()
 }
 
-fn find_matching_entries(
-    report: &[u32],
-    required_match_count: usize,
-    matches: &[u32],
-) -> Option<u32> {
-    if required_match_count <= 1 {
-        let match_sum = matches.iter().fold(0, |acc, x| acc + x);
-        return report
+/// The sum the expense report is searched for.
+pub const TARGET: u32 = 2020;
+
+/// An entry of the expense report and its 0-based index in the report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Entry {
+    pub index: usize,
+    pub value: u32,
+}
+
+fn entries(report: &[u32], indices: &[usize]) -> Vec<Entry> {
+    let mut indices = indices.to_vec();
+    indices.sort_unstable();
+
+    indices
+        .into_iter()
+        .map(|index| Entry {
+            index,
+            value: report[index],
+        })
+        .collect()
+}
+
+fn find_two_sum(report: &[u32], target: u64) -> Option<Vec<usize>> {
+    let mut seen = HashMap::new();
+
+    for (index, &value) in report.iter().enumerate() {
+        let value = value as u64;
+        if value > target {
+            continue;
+        }
+
+        if let Some(&other) = seen.get(&(target - value)) {
+            return Some(vec![other, index]);
+        }
+
+        seen.entry(value).or_insert(index);
+    }
+
+    None
+}
+
+fn find_three_sum(report: &[u32], target: u64) -> Option<Vec<usize>> {
+    let mut sorted = (0..report.len()).collect::<Vec<_>>();
+    sorted.sort_unstable_by_key(|&index| report[index]);
+
+    for (position, &first) in sorted.iter().enumerate() {
+        let mut low = position + 1;
+        let mut high = sorted.len().saturating_sub(1);
+
+        while low < high {
+            let sum =
+                report[first] as u64 + report[sorted[low]] as u64 + report[sorted[high]] as u64;
+
+            match sum.cmp(&target) {
+                Ordering::Equal => return Some(vec![first, sorted[low], sorted[high]]),
+                Ordering::Less => low += 1,
+                Ordering::Greater => high -= 1,
+            }
+        }
+    }
+
+    None
+}
+
+// Calls `f` with every combination of `size` indices into `report`, in
+// increasing order, along with the sum of their values.
+fn for_each_combination<F>(report: &[u32], size: usize, f: &mut F)
+where
+    F: FnMut(&[usize], u64),
+{
+    fn walk<F>(report: &[u32], size: usize, combination: &mut Vec<usize>, sum: u64, f: &mut F)
+    where
+        F: FnMut(&[usize], u64),
+    {
+        if combination.len() == size {
+            f(combination, sum);
+            return;
+        }
+
+        // leave enough entries after `index` to fill the rest of the combination
+        let start = combination.last().map_or(0, |index| index + 1);
+        let end = (report.len() + combination.len() + 1).saturating_sub(size);
+
+        for index in start..end {
+            combination.push(index);
+            walk(report, size, combination, sum + report[index] as u64, f);
+            combination.pop();
+        }
+    }
+
+    walk(report, size, &mut Vec::with_capacity(size), 0, f);
+}
+
+// Splits every sorted combination of `k` indices into its lowest `k / 2`
+// indices and the rest. The lower halves are indexed by their sum, keeping the
+// one that ends earliest, so each upper half only needs a single lookup.
+fn find_k_sum_meet_in_the_middle(report: &[u32], k: usize, target: u64) -> Option<Vec<usize>> {
+    let lower_size = k / 2;
+    let upper_size = k - lower_size;
+
+    let mut lower_halves: HashMap<u64, Vec<usize>> = HashMap::new();
+    for_each_combination(report, lower_size, &mut |combination, sum| {
+        if sum > target {
+            return;
+        }
+
+        let last = combination[combination.len() - 1];
+        let entry = lower_halves
+            .entry(sum)
+            .or_insert_with(|| combination.to_vec());
+        if last < entry[entry.len() - 1] {
+            *entry = combination.to_vec();
+        }
+    });
+
+    let mut found = None;
+    for_each_combination(report, upper_size, &mut |combination, sum| {
+        if found.is_some() || sum > target {
+            return;
+        }
+
+        if let Some(lower) = lower_halves.get(&(target - sum)) {
+            if lower[lower.len() - 1] < combination[0] {
+                found = Some([&lower[..], combination].concat());
+            }
+        }
+    });
+
+    found
+}
+
+/// Finds `k` distinct entries of `report` that sum to `target`, returned in
+/// report order.
+///
+/// Two entries are found with a hash lookup, three with a two pointer scan of
+/// the sorted report, and more by meeting in the middle, which takes
+/// `O(n^(k / 2))` time and memory.
+pub fn find_k_sum(report: &[u32], k: usize, target: u32) -> Option<Vec<Entry>> {
+    let target = target as u64;
+
+    let indices = match k {
+        0 => match target {
+            0 => Some(vec![]),
+            _ => None,
+        },
+        1 => report
             .iter()
-            .find(|&value| match_sum + value == 2020)
-            .map(|found| matches.iter().fold(1, |acc, x| acc * x) * found);
+            .position(|&value| value as u64 == target)
+            .map(|index| vec![index]),
+        2 => find_two_sum(report, target),
+        3 => find_three_sum(report, target),
+        _ if k > report.len() => None,
+        _ => find_k_sum_meet_in_the_middle(report, k, target),
+    };
+
+    indices.map(|indices| entries(report, &indices))
+}
+
+/// Iterator over every combination of `k` distinct entries summing to a
+/// target, created by [`k_sum_combinations`].
+pub struct KSumCombinations<'a> {
+    report: &'a [u32],
+    // indices of the report sorted by value, so a branch can be cut off as soon
+    // as its sum goes over the target
+    sorted: Vec<usize>,
+    k: usize,
+    target: u64,
+    // positions into `sorted` of the combination being built
+    stack: Vec<usize>,
+    sum: u64,
+    started: bool,
+}
+
+impl<'a> KSumCombinations<'a> {
+    fn value(&self, position: usize) -> u64 {
+        self.report[self.sorted[position]] as u64
+    }
+
+    fn can_push(&self, position: usize) -> bool {
+        position < self.sorted.len()
+            && self.sorted.len() - position >= self.k - self.stack.len()
+            && self.sum + self.value(position) <= self.target
+    }
+
+    fn push(&mut self, position: usize) {
+        self.sum += self.value(position);
+        self.stack.push(position);
+    }
+
+    // Moves to the next combination in the depth first search, returning
+    // `false` once every combination has been visited.
+    fn advance(&mut self) -> bool {
+        if !self.started {
+            self.started = true;
+
+            return match self.k {
+                0 => true,
+                _ if self.can_push(0) => {
+                    self.push(0);
+                    true
+                }
+                _ => false,
+            };
+        }
+
+        if self.stack.len() < self.k {
+            let next = self.stack.last().map_or(0, |position| position + 1);
+            if self.can_push(next) {
+                self.push(next);
+                return true;
+            }
+        }
+
+        while let Some(position) = self.stack.pop() {
+            self.sum -= self.value(position);
+
+            if self.can_push(position + 1) {
+                self.push(position + 1);
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+impl<'a> Iterator for KSumCombinations<'a> {
+    type Item = Vec<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.advance() {
+            if self.stack.len() == self.k && self.sum == self.target {
+                let indices = self
+                    .stack
+                    .iter()
+                    .map(|&position| self.sorted[position])
+                    .collect::<Vec<_>>();
+
+                return Some(entries(self.report, &indices));
+            }
+        }
+
+        None
+    }
+}
+
+/// Lazily finds every combination of `k` distinct entries of `report` that
+/// sum to `target`. Entries with the same value on different lines make
+/// different combinations.
+pub fn k_sum_combinations(report: &[u32], k: usize, target: u32) -> KSumCombinations<'_> {
+    let mut sorted = (0..report.len()).collect::<Vec<_>>();
+    sorted.sort_by_key(|&index| (report[index], index));
+
+    KSumCombinations {
+        report,
+        sorted,
+        k,
+        target: target as u64,
+        // a combination never holds more than every entry, whatever `k` is
+        stack: Vec::with_capacity(k.min(report.len())),
+        sum: 0,
+        started: false,
     }
+}
 
-    report
+/// Whether [`subset_sum`] looks for the subset with the fewest or the most
+/// entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    FewestItems,
+    MostItems,
+}
+
+impl Objective {
+    fn prefers(&self, candidate: u32, current: u32) -> bool {
+        match self {
+            Objective::FewestItems => candidate < current,
+            Objective::MostItems => candidate > current,
+        }
+    }
+}
+
+/// The outcome of [`subset_sum`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubsetSum {
+    Feasible(Vec<Entry>),
+    Infeasible,
+}
+
+/// The most memory [`subset_sum`] allocates before giving up, in bytes.
+pub const SUBSET_SUM_MEMORY_LIMIT: usize = 1 << 30;
+
+/// Finds a subset of `report`, of any size, summing to `target` with the
+/// fewest or the most entries.
+///
+/// This is the 0/1 knapsack dynamic program over every sum up to `target`,
+/// taking `O(n * target)` time. Rebuilding the subset needs one bit per entry
+/// and sum, so several thousand entries with a target in the thousands stay
+/// within a few megabytes. Inputs needing more than
+/// [`SUBSET_SUM_MEMORY_LIMIT`] are rejected rather than allocated.
+pub fn subset_sum(report: &[u32], target: u32, objective: Objective) -> AppResult<SubsetSum> {
+    let width = target as usize + 1;
+
+    // entries larger than the target can never be part of the subset
+    let candidates = report
         .iter()
         .enumerate()
-        .find_map(|(current_index, &current_value)| {
-            let updated_matches = &[matches, &[current_value]].concat();
-            let new_report = &report
-                .iter()
-                .skip(current_index)
-                .map(|&v| v)
-                .collect::<Vec<_>>();
+        .filter(|(_, &value)| value <= target)
+        .map(|(index, &value)| (index, value as usize))
+        .collect::<Vec<_>>();
 
-            find_matching_entries(new_report, required_match_count - 1, updated_matches)
-        })
-}
+    let memory = candidates
+        .len()
+        .checked_mul(width)
+        .map(|bits| bits.div_ceil(8))
+        .and_then(|taken| taken.checked_add(width.checked_mul(size_of::<Option<u32>>())?))
+        .filter(|&memory| memory <= SUBSET_SUM_MEMORY_LIMIT);
+    if memory.is_none() {
+        return Err(AppError::invalid_input(&format!(
+            "Subset sum of {} entries up to {} needs more than {} MiB",
+            candidates.len(),
+            target,
+            SUBSET_SUM_MEMORY_LIMIT >> 20
+        )));
+    }
+
+    // best[sum] is the size of the best subset of the entries seen so far
+    // summing to `sum`
+    let mut best = vec![None; width];
+    best[0] = Some(0u32);
+
+    // taken[row * width + sum] is set when the entry of that row improved
+    // best[sum], which is all that's needed to walk the subset back
+    let mut taken = vec![0u64; (candidates.len() * width).div_ceil(64)];
+
+    for (row, &(_, value)) in candidates.iter().enumerate() {
+        for sum in (value..width).rev() {
+            let candidate = match best[sum - value] {
+                Some(count) => count + 1,
+                None => continue,
+            };
+
+            let improves = match best[sum] {
+                Some(current) => objective.prefers(candidate, current),
+                None => true,
+            };
+
+            if improves {
+                best[sum] = Some(candidate);
+
+                let bit = row * width + sum;
+                taken[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+    }
+
+    if best[width - 1].is_none() {
+        return Ok(SubsetSum::Infeasible);
+    }
+
+    let mut indices = vec![];
+    let mut sum = width - 1;
+    for (row, &(index, value)) in candidates.iter().enumerate().rev() {
+        let bit = row * width + sum;
+        if taken[bit / 64] & (1 << (bit % 64)) != 0 {
+            indices.push(index);
+            sum -= value;
+        }
+    }
 
-fn find_two_matching_entries(report: &[u32]) -> Option<u32> {
-    find_matching_entries(report, 2, &vec![])
+    Ok(SubsetSum::Feasible(entries(report, &indices)))
 }
 
-fn find_three_matching_entries(report: &[u32]) -> Option<u32> {
-    find_matching_entries(report, 3, &vec![])
+/// Multiplies the values of `entries`.
+pub fn product(entries: &[Entry]) -> u64 {
+    entries.iter().map(|entry| entry.value as u64).product()
 }
 
-pub fn run() -> AppResult<()> {
-    let report = read_expense_report("data/expense-report.txt")?;
+// e.g. `Lines 1, 4: 1721 + 299 = 2020`, with 1-based line numbers
+fn write_entries<W: Write>(writer: &mut W, entries: &[Entry], target: u32) -> io::Result<()> {
+    let lines = entries
+        .iter()
+        .map(|entry| (entry.index + 1).to_string())
+        .collect::<Vec<_>>();
+    let values = entries
+        .iter()
+        .map(|entry| entry.value.to_string())
+        .collect::<Vec<_>>();
+
+    writeln!(
+        writer,
+        "Lines {}: {} = {}",
+        lines.join(", "),
+        values.join(" + "),
+        target
+    )
+}
 
-    println!(
-        "{}",
-        match find_two_matching_entries(&report) {
-            Some(code) => format!("The code for problem 1 is: \"{}\"", code),
-            None => "Could not find a match".to_string(),
+/// Writes every combination of `k` entries of `report` summing to `target`,
+/// stopping after `limit` of them, followed by how many were found.
+pub fn write_k_sums<W: Write>(
+    mut writer: W,
+    report: &[u32],
+    k: usize,
+    target: u32,
+    limit: Option<usize>,
+) -> AppResult<()> {
+    let mut count = 0;
+    let mut truncated = false;
+    for entries in k_sum_combinations(report, k, target) {
+        // the combination past the limit is only fetched to tell whether any
+        // were left out
+        if Some(count) == limit {
+            truncated = true;
+            break;
         }
-    );
 
-    println!(
-        "{}",
-        match find_three_matching_entries(&report) {
-            Some(code) => format!("The code for problem 2 is: \"{}\"", code),
-            None => "Could not find a match".to_string(),
+        write_entries(&mut writer, &entries, target)?;
+        count += 1;
+    }
+
+    match truncated {
+        true => writeln!(writer, "Stopped after {} combination(s)", count)?,
+        false => writeln!(
+            writer,
+            "{} combination(s) of {} entries sum to {}",
+            count, k, target
+        )?,
+    }
+
+    Ok(())
+}
+
+/// Writes the subset of `report` found by [`subset_sum`] and its size, or
+/// that no subset sums to `target`.
+pub fn write_subset_sum<W: Write>(
+    mut writer: W,
+    report: &[u32],
+    target: u32,
+    objective: Objective,
+) -> AppResult<()> {
+    match subset_sum(report, target, objective)? {
+        SubsetSum::Feasible(entries) => {
+            write_entries(&mut writer, &entries, target)?;
+            writeln!(writer, "{} entries", entries.len())?;
         }
-    );
+        SubsetSum::Infeasible => writeln!(writer, "Infeasible: no subset sums to {}", target)?,
+    }
 
     Ok(())
 }
+
+/// Day 1: Report Repair.
+pub struct ReportRepair;
+
+impl Solution for ReportRepair {
+    type Input = Vec<u32>;
+    type Answer = u64;
+
+    fn parse(&self, input: &str) -> AppResult<Self::Input> {
+        read_expense_report(input.as_bytes())
+    }
+
+    fn part1(&self, report: &Self::Input) -> AppResult<Self::Answer> {
+        find_k_sum(report, 2, TARGET)
+            .map(|entries| product(&entries))
+            .ok_or_else(|| AppError::unsolvable("Could not find a match"))
+    }
+
+    fn part2(&self, report: &Self::Input) -> AppResult<Self::Answer> {
+        find_k_sum(report, 3, TARGET)
+            .map(|entries| product(&entries))
+            .ok_or_else(|| AppError::unsolvable("Could not find a match"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    const EXAMPLE: &str = "1721\n979\n366\n299\n675\n1456";
+
+    #[test]
+    fn part1_example() {
+        let report = ReportRepair.parse(EXAMPLE).unwrap();
+
+        assert_eq!(ReportRepair.part1(&report).unwrap(), 514579);
+    }
+
+    #[test]
+    fn part2_example() {
+        let report = ReportRepair.parse(EXAMPLE).unwrap();
+
+        assert_eq!(ReportRepair.part2(&report).unwrap(), 241861950);
+    }
+
+    #[test]
+    fn no_matching_entries() {
+        let report = ReportRepair.parse("1\n2\n3").unwrap();
+
+        assert!(ReportRepair.part1(&report).is_err());
+    }
+
+    #[test]
+    fn k_sum_returns_the_entries() {
+        let report = ReportRepair.parse(EXAMPLE).unwrap();
+
+        assert_eq!(
+            find_k_sum(&report, 2, TARGET).unwrap(),
+            vec![
+                Entry {
+                    index: 0,
+                    value: 1721
+                },
+                Entry {
+                    index: 3,
+                    value: 299
+                },
+            ]
+        );
+        assert_eq!(
+            find_k_sum(&report, 3, TARGET)
+                .unwrap()
+                .iter()
+                .map(|entry| entry.index)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 4]
+        );
+    }
+
+    #[test]
+    fn k_sum_any_size() {
+        let report = vec![8, 1, 20, 3, 14, 5, 2, 30];
+
+        for k in 0..=report.len() {
+            for target in 0..=90 {
+                let found = find_k_sum(&report, k, target);
+                let brute_force = (0u32..(1 << report.len()))
+                    .filter(|mask| mask.count_ones() as usize == k)
+                    .any(|mask| {
+                        (0..report.len())
+                            .filter(|index| mask & (1 << index) != 0)
+                            .map(|index| report[index])
+                            .sum::<u32>()
+                            == target
+                    });
+
+                assert_eq!(
+                    found.is_some(),
+                    brute_force,
+                    "k = {}, target = {}",
+                    k,
+                    target
+                );
+                if let Some(entries) = found {
+                    assert_eq!(entries.len(), k);
+                    assert_eq!(entries.iter().map(|entry| entry.value).sum::<u32>(), target);
+                    assert!(entries.windows(2).all(|pair| pair[0].index < pair[1].index));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn k_sum_does_not_reuse_entries() {
+        assert_eq!(find_k_sum(&[1010, 5], 2, TARGET), None);
+        assert_eq!(find_k_sum(&[1010, 5, 1010], 2, TARGET).unwrap().len(), 2);
+        assert_eq!(find_k_sum(&[505, 505, 505], 4, TARGET), None);
+    }
+
+    #[test]
+    fn k_sum_larger_than_the_report() {
+        let report = vec![1721, 979, 366];
+
+        assert_eq!(k_sum_combinations(&report, 4, TARGET).count(), 0);
+        assert_eq!(k_sum_combinations(&report, usize::MAX, TARGET).count(), 0);
+    }
+
+    #[test]
+    fn every_combination() {
+        let report = vec![1010, 5, 1010, 2015, 1010, 0];
+        let combinations = k_sum_combinations(&report, 2, TARGET)
+            .map(|entries| entries.iter().map(|entry| entry.index).collect::<Vec<_>>())
+            .collect::<HashSet<_>>();
+
+        let expected = vec![vec![0, 2], vec![0, 4], vec![2, 4], vec![1, 3]];
+        assert_eq!(combinations, expected.into_iter().collect());
+    }
+
+    #[test]
+    fn every_combination_matches_brute_force() {
+        let report = vec![3, 1, 4, 1, 5, 9, 2, 6, 5, 3];
+
+        for k in 0..=4 {
+            for target in 0..=25 {
+                let found = k_sum_combinations(&report, k, target).count();
+                let brute_force = (0u32..(1 << report.len()))
+                    .filter(|mask| mask.count_ones() as usize == k)
+                    .filter(|mask| {
+                        (0..report.len())
+                            .filter(|index| mask & (1 << index) != 0)
+                            .map(|index| report[index])
+                            .sum::<u32>()
+                            == target
+                    })
+                    .count();
+
+                assert_eq!(found, brute_force, "k = {}, target = {}", k, target);
+            }
+        }
+    }
+
+    #[test]
+    fn subset_sum_fewest_and_most() {
+        let report = vec![1000, 20, 1000, 500, 520, 2000, 1, 19];
+
+        let fewest = match subset_sum(&report, TARGET, Objective::FewestItems).unwrap() {
+            SubsetSum::Feasible(entries) => entries,
+            SubsetSum::Infeasible => panic!("Expected a subset"),
+        };
+        assert_eq!(
+            fewest.iter().map(|entry| entry.index).collect::<Vec<_>>(),
+            vec![1, 5]
+        );
+
+        let most = match subset_sum(&report, TARGET, Objective::MostItems).unwrap() {
+            SubsetSum::Feasible(entries) => entries,
+            SubsetSum::Infeasible => panic!("Expected a subset"),
+        };
+        assert_eq!(
+            most.iter().map(|entry| entry.index).collect::<Vec<_>>(),
+            vec![0, 2, 6, 7]
+        );
+        assert_eq!(most.iter().map(|entry| entry.value).sum::<u32>(), TARGET);
+    }
+
+    #[test]
+    fn subset_sum_matches_brute_force() {
+        let report = vec![3, 1, 4, 1, 5, 9, 2, 6, 0, 5];
+
+        for target in 0..=40 {
+            let sizes = (0u32..(1 << report.len()))
+                .filter(|mask| {
+                    (0..report.len())
+                        .filter(|index| mask & (1 << index) != 0)
+                        .map(|index| report[index])
+                        .sum::<u32>()
+                        == target
+                })
+                .map(|mask| mask.count_ones() as usize)
+                .collect::<Vec<_>>();
+
+            for &(objective, expected) in [
+                (Objective::FewestItems, sizes.iter().min()),
+                (Objective::MostItems, sizes.iter().max()),
+            ]
+            .iter()
+            {
+                match (subset_sum(&report, target, objective).unwrap(), expected) {
+                    (SubsetSum::Feasible(entries), Some(&size)) => {
+                        assert_eq!(entries.len(), size, "target = {}", target);
+                        assert_eq!(entries.iter().map(|entry| entry.value).sum::<u32>(), target);
+                    }
+                    (SubsetSum::Infeasible, None) => (),
+                    (result, _) => panic!("Unexpected {:?} for target {}", result, target),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn subset_sum_infeasible() {
+        assert_eq!(
+            subset_sum(&[2, 4, 6], 5, Objective::FewestItems).unwrap(),
+            SubsetSum::Infeasible
+        );
+    }
+
+    #[test]
+    fn subset_sum_too_large() {
+        assert!(subset_sum(&[1, 2, 3], 4_000_000_000, Objective::FewestItems).is_err());
+    }
+
+    #[test]
+    fn written_sums() {
+        let report = ReportRepair.parse(EXAMPLE).unwrap();
+        let write = |report: &[u32], limit| {
+            let mut output = vec![];
+            write_k_sums(&mut output, report, 2, TARGET, limit).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        assert_eq!(
+            write(&report, None),
+            "Lines 1, 4: 1721 + 299 = 2020\n1 combination(s) of 2 entries sum to 2020\n"
+        );
+        assert_eq!(write(&report, Some(1)), write(&report, None));
+
+        let two_sums = [1721, 299, 1000, 1020];
+        assert_eq!(
+            write(&two_sums, Some(1)),
+            "Lines 1, 2: 1721 + 299 = 2020\nStopped after 1 combination(s)\n"
+        );
+        assert!(write(&two_sums, Some(2)).ends_with("2 combination(s) of 2 entries sum to 2020\n"));
+
+        let mut output = vec![];
+        write_subset_sum(&mut output, &report, 5, Objective::FewestItems).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Infeasible: no subset sums to 5\n"
+        );
+    }
+
+    #[test]
+    fn invalid_entry() {
+        assert!(ReportRepair.parse("1721\nabc").is_err());
+    }
+}
@@ -1,118 +1,1017 @@
-use crate::errors::AppResult;
-use std::{fs::File, io::BufRead, io::BufReader};
+use crate::errors::{AppError, AppResult, ParseError};
+use crate::report::csv_field;
+use crate::solution::Solution;
+use std::fmt;
+use std::io::{self, BufRead, Write};
 
+/// The `1-3 a` part of a line. The two numbers are read as occurrence counts
+/// by the sled rental policy and as 1-based positions by the toboggan policy.
 #[derive(Debug)]
-struct PasswordPolicy {
-    value: char,
-    min_occurrences: usize,
-    max_occurrences: usize,
+pub struct PasswordPolicy {
+    pub value: char,
+    pub min_occurrences: usize,
+    pub max_occurrences: usize,
 }
 
+impl fmt::Display for PasswordPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}-{} {}",
+            self.min_occurrences, self.max_occurrences, self.value
+        )
+    }
+}
+
+/// A line of the password database, e.g. `1-3 a: abcde`.
 #[derive(Debug)]
-struct Password {
-    value: String,
-    policy: PasswordPolicy,
-}
-
-fn read_passwords(path: &str) -> AppResult<Vec<Password>> {
-    BufReader::new(File::open(path)?)
-        .lines()
-        .map(|line| {
-            let parts = line?
-                .split_whitespace()
-                .map(str::to_string)
-                .collect::<Vec<_>>();
-
-            let occurrences = parts
-                .get(0)
-                .expect("Line missing password policy")
-                .split("-")
-                .map(|x| x.parse::<usize>().expect("Failed to parse password policy"))
-                .collect::<Vec<_>>();
-
-            let min_occurrences = *occurrences
-                .get(0)
-                .expect("Missing min occurrence in password policy");
-            let max_occurrences = *occurrences
-                .get(1)
-                .expect("Missing max occurrence in password policy");
-
-            let policy_value = parts
-                .get(1)
-                .expect("Line missing password policy value")
-                .replace(":", "")
-                .parse::<char>()
-                .expect("Could not parse policy value as character");
-
-            let value = parts.get(2).expect("Line missing password").to_owned();
-
-            Ok(Password {
-                value,
-                policy: PasswordPolicy {
-                    value: policy_value,
-                    min_occurrences,
-                    max_occurrences,
-                },
-            })
+pub struct Password {
+    // 1-based line of the database the password was read from
+    pub line: usize,
+    pub value: String,
+    pub policy: PasswordPolicy,
+}
+
+/// Parses a single line of the password database.
+pub fn parse_password(line_number: usize, line: &str) -> Result<Password, ParseError> {
+    let error = |token: &str, message: &str| -> ParseError {
+        ParseError::at_token(line_number, line, token, message)
+    };
+    let end = &line[line.len()..];
+
+    let mut parts = line.split_whitespace();
+
+    let occurrences = parts
+        .next()
+        .ok_or_else(|| error(line, "Line missing password policy"))?;
+    let (min_occurrences, max_occurrences) = match occurrences.find('-') {
+        Some(index) => (&occurrences[..index], &occurrences[(index + 1)..]),
+        None => {
+            return Err(error(
+                occurrences,
+                "Missing max occurrence in password policy",
+            ))
+        }
+    };
+
+    let min_occurrences = min_occurrences.parse::<usize>().map_err(|err| {
+        error(
+            min_occurrences,
+            "Failed to parse min occurrence in password policy",
+        )
+        .with_source(err)
+    })?;
+    let max_occurrences = max_occurrences.parse::<usize>().map_err(|err| {
+        error(
+            max_occurrences,
+            "Failed to parse max occurrence in password policy",
+        )
+        .with_source(err)
+    })?;
+
+    let policy_value = parts
+        .next()
+        .ok_or_else(|| error(end, "Line missing password policy value"))?;
+    let policy_value = match policy_value.strip_suffix(':') {
+        Some(value) => match value.parse::<char>() {
+            Ok(character) if character.is_ascii() => character,
+            Ok(character) => {
+                return Err(error(
+                    value,
+                    &format!("Policy value '{}' must be ASCII", character),
+                ))
+            }
+            Err(err) => {
+                return Err(
+                    error(value, "Could not parse policy value as character").with_source(err)
+                )
+            }
+        },
+        None => return Err(error(policy_value, "Expected ':' after policy value")),
+    };
+
+    let value = parts
+        .next()
+        .ok_or_else(|| error(end, "Line missing password"))?;
+
+    if let Some(extra) = parts.next() {
+        return Err(error(extra, "Unexpected text after password"));
+    }
+
+    Ok(Password {
+        line: line_number,
+        value: value.to_string(),
+        policy: PasswordPolicy {
+            value: policy_value,
+            min_occurrences,
+            max_occurrences,
+        },
+    })
+}
+
+/// What to do with lines of the password database that fail to parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Fail on invalid lines, with every one of them when the whole database
+    /// is read, or with the first when it's streamed.
+    Strict,
+    /// Leave invalid lines out, only reporting them as diagnostics.
+    SkipInvalid,
+}
+
+/// The passwords of a database along with the lines that failed to parse.
+#[derive(Debug, Default)]
+pub struct PasswordDatabase {
+    pub passwords: Vec<Password>,
+    pub diagnostics: Vec<ParseError>,
+}
+
+/// Parses passwords as they're read, so only a single line is held in memory.
+/// Invalid lines, including lines that aren't valid UTF-8, are
+/// `AppError::Parse` errors, and reading carries on after them. Only failing
+/// to read is fatal.
+pub struct PasswordStream<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    line_number: usize,
+}
+
+impl<R: BufRead> Iterator for PasswordStream<R> {
+    type Item = AppResult<Password>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buffer.clear();
+        self.line_number += 1;
+
+        match self.reader.read_until(b'\n', &mut self.buffer) {
+            Ok(0) => return None,
+            Ok(_) => {}
+            Err(err) => {
+                return Some(Err(AppError::io(
+                    &format!("Failed to read line {}", self.line_number),
+                    err,
+                )))
+            }
+        }
+
+        // line endings are stripped as `BufRead::lines` does
+        let mut line = self.buffer.as_slice();
+        if let Some(rest) = line.strip_suffix(b"\n") {
+            line = rest.strip_suffix(b"\r").unwrap_or(rest);
+        }
+
+        Some(match std::str::from_utf8(line) {
+            Ok(line) => parse_password(self.line_number, line).map_err(AppError::from),
+            Err(err) => {
+                let valid = String::from_utf8_lossy(&line[..err.valid_up_to()]);
+
+                Err(ParseError::new(
+                    self.line_number,
+                    valid.chars().count() + 1,
+                    "Line is not valid UTF-8",
+                )
+                .with_source(err)
+                .into())
+            }
         })
-        .collect()
+    }
 }
 
-fn sled_rental_password_policy(passwords: &[Password]) -> AppResult<u32> {
-    let invalid_password_count = passwords
-        .iter()
-        .filter(|password| {
-            let policy_value_count =
-                password
-                    .value
-                    .chars()
-                    .fold(0, |acc, x| match x == password.policy.value {
-                        true => acc + 1,
-                        false => acc,
-                    });
-
-            policy_value_count >= password.policy.min_occurrences
-                && policy_value_count <= password.policy.max_occurrences
+pub fn stream_passwords<R: BufRead>(reader: R) -> PasswordStream<R> {
+    PasswordStream {
+        reader,
+        buffer: vec![],
+        line_number: 0,
+    }
+}
+
+/// Reads one password per line, collecting a diagnostic for every invalid line
+/// instead of stopping at the first.
+pub fn read_password_database<R: BufRead>(
+    reader: R,
+    mode: ParseMode,
+) -> AppResult<PasswordDatabase> {
+    let mut database = PasswordDatabase::default();
+
+    for password in stream_passwords(reader) {
+        match password {
+            Ok(password) => database.passwords.push(password),
+            Err(AppError::Parse(err)) => database.diagnostics.push(err),
+            Err(err) => return Err(err),
+        }
+    }
+
+    match mode == ParseMode::Strict && !database.diagnostics.is_empty() {
+        true => Err(AppError::diagnostics(database.diagnostics)),
+        false => Ok(database),
+    }
+}
+
+/// Reads one password per line, failing on any invalid line.
+pub fn read_passwords<R: BufRead>(reader: R) -> AppResult<Vec<Password>> {
+    Ok(read_password_database(reader, ParseMode::Strict)?.passwords)
+}
+
+/// A rule a password has to follow.
+pub trait Policy {
+    fn is_valid(&self, password: &Password) -> bool;
+
+    /// Why `password` passes or fails, e.g. `found 2 'a', expected 1-3`.
+    fn explain(&self, password: &Password) -> String;
+}
+
+fn count_occurrences(password: &Password) -> usize {
+    password
+        .value
+        .chars()
+        .fold(0, |acc, x| match x == password.policy.value {
+            true => acc + 1,
+            false => acc,
         })
-        .count();
+}
+
+/// The sled rental policy, where the password must contain the policy
+/// character between `min_occurrences` and `max_occurrences` times.
+#[derive(Debug, Clone, Copy)]
+pub struct SledRental;
+
+impl Policy for SledRental {
+    fn is_valid(&self, password: &Password) -> bool {
+        let policy_value_count = count_occurrences(password);
+
+        policy_value_count >= password.policy.min_occurrences
+            && policy_value_count <= password.policy.max_occurrences
+    }
 
-    Ok(invalid_password_count as u32)
+    fn explain(&self, password: &Password) -> String {
+        format!(
+            "found {} '{}', expected {}-{}",
+            count_occurrences(password),
+            password.policy.value,
+            password.policy.min_occurrences,
+            password.policy.max_occurrences
+        )
+    }
 }
 
-fn toboggan_corporate_password_policy(passwords: &[Password]) -> AppResult<u32> {
-    let invalid_password_count = passwords
+/// The Official Toboggan Corporate policy, where the policy character must be
+/// at exactly one of the two 1-based positions.
+#[derive(Debug, Clone, Copy)]
+pub struct TobogganCorporate;
+
+impl TobogganCorporate {
+    // positions past the end of the password, or the 0th position, never hold
+    // the policy character
+    fn at_position(password: &Password, position: usize) -> Option<char> {
+        position
+            .checked_sub(1)
+            .and_then(|index| password.value.chars().nth(index))
+    }
+}
+
+impl Policy for TobogganCorporate {
+    fn is_valid(&self, password: &Password) -> bool {
+        let Password { policy, .. } = password;
+        let has_value =
+            |position: usize| Self::at_position(password, position) == Some(policy.value);
+
+        has_value(policy.min_occurrences) ^ has_value(policy.max_occurrences)
+    }
+
+    fn explain(&self, password: &Password) -> String {
+        let Password { policy, .. } = password;
+        let positions = [policy.min_occurrences, policy.max_occurrences];
+        let describe = |position: usize| match Self::at_position(password, position) {
+            Some(value) => format!("{} is '{}'", position, value),
+            None => format!("{} is out of range", position),
+        };
+        let matched = positions
+            .iter()
+            .filter(|&&position| Self::at_position(password, position) == Some(policy.value))
+            .count();
+
+        format!(
+            "{} '{}': {}, {}",
+            match matched {
+                0 => "neither position holds",
+                1 => "one position holds",
+                _ => "both positions hold",
+            },
+            policy.value,
+            describe(positions[0]),
+            describe(positions[1])
+        )
+    }
+}
+
+/// Requires at least the given number of characters.
+#[derive(Debug, Clone, Copy)]
+pub struct MinLength(pub usize);
+
+impl Policy for MinLength {
+    fn is_valid(&self, password: &Password) -> bool {
+        password.value.chars().count() >= self.0
+    }
+
+    fn explain(&self, password: &Password) -> String {
+        format!(
+            "length {}, expected at least {}",
+            password.value.chars().count(),
+            self.0
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharClass {
+    Lowercase,
+    Uppercase,
+    Digit,
+    // anything that isn't alphanumeric
+    Symbol,
+}
+
+impl CharClass {
+    pub fn contains(&self, value: char) -> bool {
+        match self {
+            CharClass::Lowercase => value.is_lowercase(),
+            CharClass::Uppercase => value.is_uppercase(),
+            CharClass::Digit => value.is_ascii_digit(),
+            CharClass::Symbol => !value.is_alphanumeric(),
+        }
+    }
+
+    /// The name used for the class by [`parse_policy`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            CharClass::Lowercase => "lower",
+            CharClass::Uppercase => "upper",
+            CharClass::Digit => "digit",
+            CharClass::Symbol => "symbol",
+        }
+    }
+}
+
+/// Requires at least one character of every class.
+#[derive(Debug, Clone)]
+pub struct RequiredClasses(pub Vec<CharClass>);
+
+impl RequiredClasses {
+    fn missing<'a>(&'a self, password: &'a Password) -> impl Iterator<Item = &'a CharClass> {
+        self.0
+            .iter()
+            .filter(move |class| !password.value.chars().any(|value| class.contains(value)))
+    }
+}
+
+impl Policy for RequiredClasses {
+    fn is_valid(&self, password: &Password) -> bool {
+        self.missing(password).next().is_none()
+    }
+
+    fn explain(&self, password: &Password) -> String {
+        let missing = self
+            .missing(password)
+            .map(|class| class.name())
+            .collect::<Vec<_>>();
+
+        match missing.is_empty() {
+            true => "has every required class".to_string(),
+            false => format!("missing {}", missing.join(", ")),
+        }
+    }
+}
+
+/// Rejects passwords containing any of the substrings.
+#[derive(Debug, Clone)]
+pub struct ForbiddenSubstrings(pub Vec<String>);
+
+impl Policy for ForbiddenSubstrings {
+    fn is_valid(&self, password: &Password) -> bool {
+        !self
+            .0
+            .iter()
+            .any(|substring| password.value.contains(substring.as_str()))
+    }
+
+    fn explain(&self, password: &Password) -> String {
+        let found = self
+            .0
+            .iter()
+            .filter(|substring| password.value.contains(substring.as_str()))
+            .map(|substring| format!("\"{}\"", substring))
+            .collect::<Vec<_>>();
+
+        match found.is_empty() {
+            true => "contains no forbidden text".to_string(),
+            false => format!("contains {}", found.join(", ")),
+        }
+    }
+}
+
+// Explains a composed policy by the policies that decided its verdict, or all
+// of them when they all agree.
+fn explain_deciding(policies: &[Box<dyn Policy>], password: &Password, verdict: bool) -> String {
+    let reasons = policies
         .iter()
-        .filter(|password| {
-            let Password { value, policy } = password;
-            let first = value
-                .chars()
-                .nth(policy.min_occurrences - 1)
-                .expect("Failed to parse password");
-
-            let second = value
-                .chars()
-                .nth(policy.max_occurrences - 1)
-                .expect("Failed to parse password");
-
-            (first == policy.value) ^ (second == policy.value)
-        })
-        .count();
+        .filter(|policy| policy.is_valid(password) == verdict)
+        .map(|policy| policy.explain(password))
+        .collect::<Vec<_>>();
+
+    reasons.join("; ")
+}
+
+/// Valid when every policy is.
+pub struct AllOf(pub Vec<Box<dyn Policy>>);
+
+impl Policy for AllOf {
+    fn is_valid(&self, password: &Password) -> bool {
+        self.0.iter().all(|policy| policy.is_valid(password))
+    }
+
+    fn explain(&self, password: &Password) -> String {
+        explain_deciding(&self.0, password, self.is_valid(password))
+    }
+}
+
+/// Valid when at least one policy is.
+pub struct AnyOf(pub Vec<Box<dyn Policy>>);
+
+impl Policy for AnyOf {
+    fn is_valid(&self, password: &Password) -> bool {
+        self.0.iter().any(|policy| policy.is_valid(password))
+    }
+
+    fn explain(&self, password: &Password) -> String {
+        explain_deciding(&self.0, password, self.is_valid(password))
+    }
+}
+
+/// Valid when none of the policies are.
+pub struct NoneOf(pub Vec<Box<dyn Policy>>);
+
+impl Policy for NoneOf {
+    fn is_valid(&self, password: &Password) -> bool {
+        !self.0.iter().any(|policy| policy.is_valid(password))
+    }
 
-    Ok(invalid_password_count as u32)
+    fn explain(&self, password: &Password) -> String {
+        explain_deciding(&self.0, password, !self.is_valid(password))
+    }
 }
 
-pub fn run() -> AppResult<()> {
-    let passwords = read_passwords("data/passwords.txt")?;
+/// Counts the passwords valid under `policy`.
+pub fn count_valid(passwords: &[Password], policy: &dyn Policy) -> usize {
+    passwords
+        .iter()
+        .filter(|password| policy.is_valid(password))
+        .count()
+}
+
+/// The verdict of a policy on a single password, with the reason for it.
+#[derive(Debug)]
+pub struct Audit<'a> {
+    pub password: &'a Password,
+    pub valid: bool,
+    pub reason: String,
+}
+
+impl<'a> Audit<'a> {
+    pub fn new(password: &'a Password, policy: &dyn Policy) -> Self {
+        Audit {
+            password,
+            valid: policy.is_valid(password),
+            reason: policy.explain(password),
+        }
+    }
+}
+
+/// Checks every password against `policy`, explaining each verdict.
+pub fn audit<'a>(passwords: &'a [Password], policy: &dyn Policy) -> Vec<Audit<'a>> {
+    passwords
+        .iter()
+        .map(|password| Audit::new(password, policy))
+        .collect()
+}
+
+fn verdict(valid: bool) -> &'static str {
+    match valid {
+        true => "valid",
+        false => "invalid",
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditFormat {
+    /// One line per audit, e.g. `line 1: 1-3 a: abcde valid (found 1 'a',
+    /// expected 1-3)`.
+    Text,
+    /// CSV with a `line,policy,password,verdict,reason` header.
+    Csv,
+}
+
+/// Writes audits one at a time, as the passwords are read.
+pub struct AuditWriter<W: Write> {
+    writer: W,
+    format: AuditFormat,
+}
+
+impl<W: Write> AuditWriter<W> {
+    pub fn new(mut writer: W, format: AuditFormat) -> io::Result<Self> {
+        if format == AuditFormat::Csv {
+            writeln!(writer, "line,policy,password,verdict,reason")?;
+        }
+
+        Ok(AuditWriter { writer, format })
+    }
+
+    pub fn write(&mut self, audit: &Audit) -> io::Result<()> {
+        let Audit {
+            password,
+            valid,
+            reason,
+        } = audit;
+
+        match self.format {
+            AuditFormat::Text => writeln!(
+                self.writer,
+                "line {}: {}: {} {} ({})",
+                password.line,
+                password.policy,
+                password.value,
+                verdict(*valid),
+                reason
+            ),
+            AuditFormat::Csv => writeln!(
+                self.writer,
+                "{},{},{},{},{}",
+                password.line,
+                csv_field(&password.policy.to_string()),
+                csv_field(&password.value),
+                verdict(*valid),
+                csv_field(reason)
+            ),
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// How many passwords [`check_stream`] checked between each
+/// [`Progress::Checked`].
+pub const PROGRESS_INTERVAL: usize = 1_000_000;
+
+/// What [`check_stream`] reports as it goes.
+#[derive(Debug)]
+pub enum Progress {
+    /// A line was skipped as it failed to parse.
+    Skipped(ParseError),
+    /// Another [`PROGRESS_INTERVAL`] passwords were checked, this many in all.
+    Checked(usize),
+}
+
+/// How many passwords were checked, how many of them were valid, and how many
+/// invalid lines were skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Tally {
+    pub checked: usize,
+    pub valid: usize,
+    pub skipped: usize,
+}
+
+/// Checks every password of `reader` against `policy` as it's read, so
+/// password dumps larger than memory can be checked, writing the audit of
+/// each to `writer` in the `audit` format.
+///
+/// In strict `mode`, stops at the first invalid line rather than collecting
+/// them all.
+pub fn check_stream<R: BufRead, W: Write>(
+    reader: R,
+    writer: W,
+    policy: &dyn Policy,
+    mode: ParseMode,
+    audit: Option<AuditFormat>,
+    mut report: impl FnMut(Progress),
+) -> AppResult<Tally> {
+    let mut writer = match audit {
+        Some(format) => Some(AuditWriter::new(writer, format)?),
+        None => None,
+    };
+    let mut tally = Tally::default();
+
+    for password in stream_passwords(reader) {
+        let password = match password {
+            Err(AppError::Parse(err)) => match mode {
+                ParseMode::Strict => return Err(err.into()),
+                ParseMode::SkipInvalid => {
+                    tally.skipped += 1;
+                    report(Progress::Skipped(err));
+                    continue;
+                }
+            },
+            password => password?,
+        };
+
+        let audit = Audit::new(&password, policy);
+        if let Some(writer) = writer.as_mut() {
+            writer.write(&audit)?;
+        }
+
+        tally.checked += 1;
+        if audit.valid {
+            tally.valid += 1;
+        }
+
+        if tally.checked % PROGRESS_INTERVAL == 0 {
+            report(Progress::Checked(tally.checked));
+        }
+    }
+
+    if let Some(writer) = writer.as_mut() {
+        writer.flush()?;
+    }
+
+    Ok(tally)
+}
+
+// grammar
+//
+// policy = sled | toboggan
+//        | min-length=<NUMBER>
+//        | classes=<class>[+...class]
+//        | forbid=<STRING>[+...STRING]
+//        | <all|any|none>(<policy>[, ...policy])
+// class = lower | upper | digit | symbol
+//
+// There's no quoting, so a STRING is never empty and can't contain `(`, `)`,
+// `,` or `+`, nor start or end with whitespace.
+struct PolicyParser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> PolicyParser<'a> {
+    fn error(&self, position: usize, message: &str) -> AppError {
+        ParseError::at_offset(self.input, position, message).into()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    // Reads up to the next delimiter of the grammar.
+    fn word(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let rest = &self.input[self.position..];
+        let length = rest.find(&['(', ')', ','][..]).unwrap_or(rest.len());
+        self.position += length;
+
+        rest[..length].trim_end()
+    }
+
+    fn expect(&mut self, delimiter: char) -> AppResult<()> {
+        self.skip_whitespace();
+        match self.input[self.position..].starts_with(delimiter) {
+            true => {
+                self.position += 1;
+                Ok(())
+            }
+            false => Err(self.error(self.position, &format!("Expected '{}'", delimiter))),
+        }
+    }
+
+    fn policy(&mut self) -> AppResult<Box<dyn Policy>> {
+        let start = self.position;
+        let word = self.word();
+        let (name, argument) = match word.find('=') {
+            Some(index) => (&word[..index], Some(&word[(index + 1)..])),
+            None => (word, None),
+        };
+
+        match (name, argument) {
+            ("sled", None) => Ok(Box::new(SledRental)),
+            ("toboggan", None) => Ok(Box::new(TobogganCorporate)),
+            ("min-length", Some(length)) => match length.parse::<usize>() {
+                Ok(length) => Ok(Box::new(MinLength(length))),
+                Err(_) => Err(self.error(start, &format!("Invalid length \"{}\"", length))),
+            },
+            ("classes", Some(classes)) => Ok(Box::new(RequiredClasses(
+                classes
+                    .split('+')
+                    .map(|class| match class {
+                        "lower" => Ok(CharClass::Lowercase),
+                        "upper" => Ok(CharClass::Uppercase),
+                        "digit" => Ok(CharClass::Digit),
+                        "symbol" => Ok(CharClass::Symbol),
+                        class => Err(self.error(start, &format!("Unknown class \"{}\"", class))),
+                    })
+                    .collect::<AppResult<Vec<_>>>()?,
+            ))),
+            // every password contains the empty string, so it would reject
+            // them all
+            ("forbid", Some(substrings)) => Ok(Box::new(ForbiddenSubstrings(
+                substrings
+                    .split('+')
+                    .map(|substring| match substring.is_empty() {
+                        true => Err(ParseError::at_slice(
+                            self.input,
+                            substring,
+                            "Expected text to forbid",
+                        )
+                        .into()),
+                        false => Ok(substring.to_string()),
+                    })
+                    .collect::<AppResult<Vec<_>>>()?,
+            ))),
+            ("all", None) => Ok(Box::new(AllOf(self.policies()?))),
+            ("any", None) => Ok(Box::new(AnyOf(self.policies()?))),
+            ("none", None) => Ok(Box::new(NoneOf(self.policies()?))),
+            ("", _) => Err(self.error(start, "Expected a policy")),
+            _ => Err(self.error(start, &format!("Unknown policy \"{}\"", word))),
+        }
+    }
+
+    fn policies(&mut self) -> AppResult<Vec<Box<dyn Policy>>> {
+        self.expect('(')?;
+        let mut policies = vec![self.policy()?];
+
+        loop {
+            self.skip_whitespace();
+            match self.input[self.position..].chars().next() {
+                Some(',') => {
+                    self.position += 1;
+                    policies.push(self.policy()?);
+                }
+                Some(')') => {
+                    self.position += 1;
+                    return Ok(policies);
+                }
+                _ => return Err(self.error(self.position, "Expected ',' or ')'")),
+            }
+        }
+    }
+}
+
+/// Builds a policy from its name, e.g. `sled` or
+/// `all(toboggan, min-length=8, classes=lower+digit, none(forbid=password))`.
+pub fn parse_policy(input: &str) -> AppResult<Box<dyn Policy>> {
+    let mut parser = PolicyParser { input, position: 0 };
+    let policy = parser.policy()?;
+
+    parser.skip_whitespace();
+    match parser.position == input.len() {
+        true => Ok(policy),
+        false => Err(parser.error(parser.position, "Unexpected input after policy")),
+    }
+}
+
+/// Day 2: Password Philosophy.
+pub struct PasswordPhilosophy;
+
+impl Solution for PasswordPhilosophy {
+    type Input = Vec<Password>;
+    type Answer = usize;
+
+    fn parse(&self, input: &str) -> AppResult<Self::Input> {
+        read_passwords(input.as_bytes())
+    }
+
+    fn part1(&self, passwords: &Self::Input) -> AppResult<Self::Answer> {
+        Ok(count_valid(passwords, &SledRental))
+    }
+
+    fn part2(&self, passwords: &Self::Input) -> AppResult<Self::Answer> {
+        Ok(count_valid(passwords, &TobogganCorporate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc";
+
+    #[test]
+    fn part1_example() {
+        let passwords = PasswordPhilosophy.parse(EXAMPLE).unwrap();
+
+        assert_eq!(PasswordPhilosophy.part1(&passwords).unwrap(), 2);
+    }
+
+    #[test]
+    fn part2_example() {
+        let passwords = PasswordPhilosophy.parse(EXAMPLE).unwrap();
+
+        assert_eq!(PasswordPhilosophy.part2(&passwords).unwrap(), 1);
+    }
+
+    #[test]
+    fn composed_policies() {
+        let passwords = read_passwords(
+            "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n1-2 x: Hunter2!\n1-1 p: password1"
+                .as_bytes(),
+        )
+        .unwrap();
+        let valid = |policy: &str| {
+            let policy = parse_policy(policy).unwrap();
+
+            passwords
+                .iter()
+                .map(|password| policy.is_valid(password))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(valid("min-length=6"), vec![false, false, true, true, true]);
+        assert_eq!(
+            valid("classes=upper+digit+symbol"),
+            vec![false, false, false, true, false]
+        );
+        assert_eq!(
+            valid("forbid=pass+cd"),
+            vec![false, false, true, true, false]
+        );
+        assert_eq!(
+            valid("all(sled, min-length=5)"),
+            vec![true, false, true, false, true]
+        );
+        assert_eq!(
+            valid("any(toboggan, classes=digit)"),
+            vec![true, false, false, true, true]
+        );
+        assert_eq!(
+            valid("none(sled, classes=digit)"),
+            vec![false, true, false, false, false]
+        );
+    }
+
+    #[test]
+    fn unknown_policy() {
+        assert!(parse_policy("all(sled, bogus)").is_err());
+        assert!(parse_policy("all(sled").is_err());
+        assert!(parse_policy("sled)").is_err());
+        assert!(parse_policy("classes=lower+emoji").is_err());
+    }
+
+    #[test]
+    fn empty_forbidden_text() {
+        for (policy, column) in [
+            ("forbid=", 8),
+            ("forbid=a++b", 10),
+            ("all(sled, forbid=a+)", 20),
+        ]
+        .iter()
+        {
+            match parse_policy(policy) {
+                Err(AppError::Parse(err)) => {
+                    assert_eq!((err.line, err.column), (1, *column), "{}", policy);
+                    assert_eq!(err.message, "Expected text to forbid");
+                }
+                _ => panic!("Expected a parse error for {}", policy),
+            }
+        }
+    }
+
+    #[test]
+    fn missing_separator() {
+        assert!(PasswordPhilosophy.parse("1-3 a abcde").is_err());
+    }
+
+    #[test]
+    fn audit_reasons() {
+        let passwords = PasswordPhilosophy
+            .parse("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n1-2 x: Hunter2!")
+            .unwrap();
+        let reasons = |policy: &str| {
+            audit(&passwords, parse_policy(policy).unwrap().as_ref())
+                .into_iter()
+                .map(|audit| (audit.valid, audit.reason))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            reasons("sled")[1],
+            (false, "found 0 'b', expected 1-3".to_string())
+        );
+        assert_eq!(
+            reasons("toboggan")[2],
+            (
+                false,
+                "both positions hold 'c': 2 is 'c', 9 is 'c'".to_string()
+            )
+        );
+        assert_eq!(
+            reasons("all(classes=digit+symbol, min-length=8)")[3],
+            (
+                true,
+                "has every required class; length 8, expected at least 8".to_string()
+            )
+        );
+        assert_eq!(
+            reasons("any(forbid=cd, classes=upper)")[1],
+            (false, "contains \"cd\"; missing upper".to_string())
+        );
+    }
+
+    #[test]
+    fn audit_csv() {
+        let passwords = PasswordPhilosophy.parse("1-3 a: ab,\"c\n1-3 b: 2").unwrap();
+        let mut csv = vec![];
+        let mut writer = AuditWriter::new(&mut csv, AuditFormat::Csv).unwrap();
+
+        for audit in audit(&passwords, &TobogganCorporate) {
+            writer.write(&audit).unwrap();
+        }
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "line,policy,password,verdict,reason\n\
+             1,1-3 a,\"ab,\"\"c\",valid,\"one position holds 'a': 1 is 'a', 3 is ','\"\n\
+             2,1-3 b,2,invalid,\"neither position holds 'b': 1 is '2', 3 is out of range\"\n"
+        );
+    }
+
+    #[test]
+    fn diagnostics_for_every_invalid_line() {
+        let input =
+            "1-3 a: abcde\n1-3 a abc\n1-x b: cdefg\n2-9 é: ééé\n2-9 c: ccccccccc\n1-3 a: ab cd";
+        let database = read_password_database(input.as_bytes(), ParseMode::SkipInvalid).unwrap();
+
+        assert_eq!(database.passwords.len(), 2);
+        assert_eq!(database.passwords[1].line, 5);
+        assert_eq!(
+            database
+                .diagnostics
+                .iter()
+                .map(|err| (err.line, err.column))
+                .collect::<Vec<_>>(),
+            vec![(2, 5), (3, 3), (4, 5), (6, 11)]
+        );
+
+        match read_password_database(input.as_bytes(), ParseMode::Strict) {
+            Err(AppError::Diagnostics(errors)) => assert_eq!(errors.len(), 4),
+            result => panic!("Expected diagnostics, found {:?}", result.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn streamed_checks() {
+        let input = b"1-3 a: abcde\nbad\n1-3 b: cdefg\r\n1-3 c: \xff\xfe\nworse";
+        let check = |mode| {
+            let mut output = vec![];
+            let mut skipped = vec![];
+            let tally = check_stream(
+                &input[..],
+                &mut output,
+                &SledRental,
+                mode,
+                Some(AuditFormat::Csv),
+                |progress| {
+                    if let Progress::Skipped(err) = progress {
+                        skipped.push((err.line, err.column))
+                    }
+                },
+            );
+
+            (
+                tally,
+                String::from_utf8(output).unwrap().lines().count(),
+                skipped,
+            )
+        };
+
+        let (tally, rows, skipped) = check(ParseMode::SkipInvalid);
+        assert_eq!(
+            tally.unwrap(),
+            Tally {
+                checked: 2,
+                valid: 1,
+                skipped: 3
+            }
+        );
+        assert_eq!((rows, skipped), (3, vec![(2, 1), (4, 8), (5, 1)]));
 
-    println!(
-        "There are {} invalid sled rental password(s)",
-        sled_rental_password_policy(&passwords)?
-    );
+        match check(ParseMode::Strict) {
+            (Err(AppError::Parse(err)), rows, skipped) => {
+                assert_eq!(err.line, 2);
+                assert_eq!((rows, skipped), (2, vec![]));
+            }
+            (result, ..) => panic!("Expected a parse error, found {:?}", result),
+        }
+    }
 
-    println!(
-        "There are {} invalid toboggan corporate password(s)",
-        toboggan_corporate_password_policy(&passwords)?
-    );
+    #[test]
+    fn toboggan_positions_out_of_range() {
+        let passwords = read_passwords("1-9 a: abc\n0-2 b: abc\n4-5 c: abc".as_bytes()).unwrap();
 
-    Ok(())
+        assert_eq!(
+            passwords
+                .iter()
+                .map(|password| TobogganCorporate.is_valid(password))
+                .collect::<Vec<_>>(),
+            vec![true, true, false]
+        );
+    }
 }
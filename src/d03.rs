@@ -1,116 +1,504 @@
-use crate::errors::AppResult;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
 
-const INPUT: &'static str = include_str!("../data/toboggan-map.txt");
+use crate::errors::{AppError, AppResult};
+use crate::grid::{FromChar, TilingGrid};
+use crate::solution::Solution;
 
-#[derive(Debug)]
-enum Cell {
+/// A square of the map, `#` for a tree and `.` for open ground.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
     Tree,
     Empty,
 }
 
-#[derive(Debug)]
-struct Position {
-    x: usize,
-    y: usize,
+impl FromChar for Cell {
+    fn from_char(value: char) -> Option<Self> {
+        match value {
+            '#' => Some(Cell::Tree),
+            '.' => Some(Cell::Empty),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug)]
-struct Board {
-    cells: Vec<Vec<Cell>>,
-    height: usize,
-    width: usize,
+/// The map of the slope, which repeats to the right forever.
+pub type Board = TilingGrid<Cell>;
+
+/// Moves `run` squares right and `rise` squares down at every step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Slope {
+    pub rise: usize,
+    pub run: usize,
 }
 
-impl Board {
-    pub fn new(cells: Vec<Vec<Cell>>) -> Board {
-        let height = cells.len();
-        let width = cells
-            .get(0)
-            .expect("Cells must contain at least 1 row")
-            .len();
-        Board {
-            cells,
-            height,
-            width,
-        }
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.run, self.rise)
     }
 }
 
-#[derive(Debug)]
-struct Slope {
-    rise: usize,
-    run: usize,
+// A slope that never goes down would never reach the bottom of the map.
+fn check_rise(slopes: &[Slope]) -> AppResult<()> {
+    match slopes.iter().any(|slope| slope.rise == 0) {
+        true => Err(AppError::invalid_input(
+            "Slopes must have a rise of at least 1",
+        )),
+        false => Ok(()),
+    }
 }
 
-fn calculate_total_collisions(board: &Board, slope: &Slope) -> u64 {
-    let mut position = Position { x: 0, y: 0 };
+/// Counts the trees hit going down `board` from the top left corner. Fails
+/// when `rise` is 0.
+pub fn calculate_total_collisions(board: &Board, slope: &Slope) -> AppResult<u64> {
+    check_rise(&[*slope])?;
+    let height = board.height() as isize;
 
-    let mut collision_count = 0;
+    Ok(board
+        .slope((0, 0), (slope.run as isize, slope.rise as isize))
+        .take_while(|&((_, y), _)| y < height)
+        .filter(|&(_, &cell)| cell == Cell::Tree)
+        .count() as u64)
+}
 
-    while position.y < board.height {
-        position = Position {
-            x: (position.x + slope.run) % board.width,
-            y: position.y + slope.rise,
-        };
+// Slopes with the same rise whose runs differ by a multiple of the map's
+// width visit exactly the same cells, so they collapse onto the smallest run.
+// A multiple of a slope, such as (2, 2) of (1, 1), only visits some of the
+// cells the slope does, every other one here, so it can hit fewer trees and
+// is counted on its own.
+fn canonical_slope(board: &Board, slope: &Slope) -> Slope {
+    Slope {
+        run: slope.run % board.width(),
+        rise: slope.rise,
+    }
+}
+
+/// A slope of a search, along with the trees hit going down it and the
+/// slopes considered equivalent to it.
+#[derive(Debug)]
+pub struct SlopeCount {
+    pub slope: Slope,
+    pub trees: u64,
+    pub equivalent: Vec<Slope>,
+}
 
-        if position.y >= board.height {
-            return collision_count;
+/// Counts the trees down every slope with a run of `0..=bound.run` and a
+/// rise of `1..=bound.rise`, counting equivalent slopes only once.
+///
+/// Slopes are equivalent only when they visit identical cells, which is when
+/// they have the same rise and their runs differ by a multiple of the map's
+/// width. (2, 2) isn't equivalent to (1, 1): it lands on every other cell of
+/// (1, 1)'s path, skipping the odd rows, so its tree count can differ.
+///
+/// Returns the slopes ordered from fewest to most trees, ties broken by the
+/// slope itself.
+pub fn search_slopes(board: &Board, bound: &Slope) -> AppResult<Vec<SlopeCount>> {
+    check_rise(&[*bound])?;
+
+    let mut groups: HashMap<Slope, Vec<Slope>> = HashMap::new();
+    for rise in 1..=bound.rise {
+        for run in 0..=bound.run {
+            let slope = Slope { run, rise };
+
+            groups
+                .entry(canonical_slope(board, &slope))
+                .or_default()
+                .push(slope);
         }
+    }
 
-        let cell = board
-            .cells
-            .get(position.y)
-            .expect("Out of bounds of map height")
-            .get(position.x)
-            .expect("Out of bounds of map width");
+    let mut counts = groups
+        .into_iter()
+        .map(|(slope, mut equivalent)| {
+            equivalent.retain(|other| *other != slope);
 
-        collision_count += match cell {
-            Cell::Tree => 1,
-            _ => 0,
-        };
+            Ok(SlopeCount {
+                trees: calculate_total_collisions(board, &slope)?,
+                slope,
+                equivalent,
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+
+    counts.sort_by_key(|count| (count.trees, count.slope.rise, count.slope.run));
+
+    Ok(counts)
+}
+
+// e.g. `Fewest (2 trees):` followed by a line per slope
+fn write_slopes<W: Write>(writer: &mut W, label: &str, counts: &[&SlopeCount]) -> AppResult<()> {
+    writeln!(writer, "{} ({} trees):", label, counts[0].trees)?;
+
+    for count in counts {
+        let equivalent = count
+            .equivalent
+            .iter()
+            .map(|slope| slope.to_string())
+            .collect::<Vec<_>>();
+
+        match equivalent.is_empty() {
+            true => writeln!(writer, "  {}", count.slope)?,
+            false => writeln!(
+                writer,
+                "  {}, same as {}",
+                count.slope,
+                equivalent.join(", ")
+            )?,
+        }
     }
 
-    collision_count
+    Ok(())
 }
 
-pub fn run() -> AppResult<()> {
-    let cells = INPUT
-        .split("\n")
-        .map(|line| {
-            line.chars()
-                .map(|character| match character {
-                    '.' => Cell::Empty,
-                    '#' => Cell::Tree,
-                    value => panic!(format!("Unknown value \"{}\" in map", value)),
-                })
-                .collect::<Vec<_>>()
-        })
+/// Writes the slopes of [`search_slopes`] hitting the fewest and the most
+/// trees, followed by how many distinct slopes were searched and a note on
+/// which slopes count as the same.
+pub fn write_slope_search<W: Write>(mut writer: W, board: &Board, bound: &Slope) -> AppResult<()> {
+    let counts = search_slopes(board, bound)?;
+    let (fewest, most) = match (counts.first(), counts.last()) {
+        (Some(fewest), Some(most)) => (fewest.trees, most.trees),
+        _ => return Err(AppError::invalid_input("No slopes to search")),
+    };
+    let with_trees = |trees| {
+        counts
+            .iter()
+            .filter(|count| count.trees == trees)
+            .collect::<Vec<_>>()
+    };
+
+    write_slopes(&mut writer, "Fewest", &with_trees(fewest))?;
+    write_slopes(&mut writer, "Most", &with_trees(most))?;
+    writeln!(
+        writer,
+        "{} distinct of {} slopes searched",
+        counts.len(),
+        (bound.run + 1) * bound.rise
+    )?;
+    writeln!(
+        writer,
+        "Slopes are the same only when they visit identical cells, so (2, 2), \
+         which skips every other cell of (1, 1), is counted apart from it"
+    )?;
+
+    Ok(())
+}
+
+// How wide the map has to be repeated for the longest of the paths down
+// `slopes`, a whole number of times.
+fn overlay_width(board: &Board, slopes: &[Slope]) -> AppResult<usize> {
+    check_rise(slopes)?;
+
+    let too_wide = || AppError::invalid_input("Paths are too wide to render");
+    let last_row = board.height() - 1;
+    let furthest = slopes
+        .iter()
+        .map(|slope| (last_row / slope.rise).checked_mul(slope.run))
+        .try_fold(0, |furthest, x| x.map(|x| furthest.max(x)))
+        .ok_or_else(too_wide)?;
+
+    (furthest / board.width() + 1)
+        .checked_mul(board.width())
+        .filter(|&width| width <= isize::MAX as usize)
+        .ok_or_else(too_wide)
+}
+
+// Every cell of row `y` of the map repeated to `width`, along with which
+// slope, if any, visits it. Where paths cross, the last slope wins.
+fn overlay_row<'a>(
+    board: &'a Board,
+    slopes: &[Slope],
+    width: usize,
+    y: usize,
+) -> impl Iterator<Item = (Cell, Option<usize>)> + 'a {
+    // paths start above the first row, so they never visit it
+    let visits = slopes
+        .iter()
+        .enumerate()
+        .filter(|(_, slope)| y > 0 && y.is_multiple_of(slope.rise))
+        .map(|(index, slope)| (y / slope.rise * slope.run, index))
         .collect::<Vec<_>>();
 
-    let board = Board::new(cells);
+    (0..width).map(move |x| {
+        let visit = visits
+            .iter()
+            .rev()
+            .find(|&&(visited, _)| visited == x)
+            .map(|&(_, index)| index);
 
-    let collision_count_1 = calculate_total_collisions(&board, &Slope { run: 1, rise: 1 });
+        (*board.at((x as isize, y as isize)), visit)
+    })
+}
 
-    let collision_count_2 = calculate_total_collisions(&board, &Slope { run: 3, rise: 1 });
+/// Writes the map with the paths down `slopes` drawn over it, `O` for open
+/// ground passed and `X` for trees hit. The map is repeated to the right for
+/// as long as the paths need.
+pub fn render_ascii<W: Write>(mut writer: W, board: &Board, slopes: &[Slope]) -> AppResult<()> {
+    let width = overlay_width(board, slopes)?;
+
+    for y in 0..board.height() {
+        let line = overlay_row(board, slopes, width, y)
+            .map(|cell| match cell {
+                (Cell::Tree, Some(_)) => 'X',
+                (Cell::Empty, Some(_)) => 'O',
+                (Cell::Tree, None) => '#',
+                (Cell::Empty, None) => '.',
+            })
+            .collect::<String>();
+
+        writeln!(writer, "{}", line)?;
+    }
+
+    Ok(())
+}
+
+const OPEN_COLOR: [u8; 3] = [236, 240, 241];
+const TREE_COLOR: [u8; 3] = [39, 110, 60];
+// cycled through when there are more slopes than colors
+const PATH_COLORS: [[u8; 3]; 6] = [
+    [231, 76, 60],
+    [52, 152, 219],
+    [243, 156, 18],
+    [155, 89, 182],
+    [26, 188, 156],
+    [241, 196, 15],
+];
+
+/// Writes the map with the paths down `slopes` drawn over it as a binary PPM
+/// image, each slope in its own color. Trees hit are drawn in a darker shade
+/// of the slope's color, and every cell is `scale` pixels square.
+pub fn render_ppm<W: Write>(
+    mut writer: W,
+    board: &Board,
+    slopes: &[Slope],
+    scale: usize,
+) -> AppResult<()> {
+    if scale == 0 {
+        return Err(AppError::invalid_input("Scale must be at least 1"));
+    }
 
-    let collision_count_3 = calculate_total_collisions(&board, &Slope { run: 5, rise: 1 });
-    let collision_count_4 = calculate_total_collisions(&board, &Slope { run: 7, rise: 1 });
+    let width = overlay_width(board, slopes)?;
+    let image_width = width
+        .checked_mul(scale)
+        .ok_or_else(|| AppError::invalid_input("Paths are too wide to render"))?;
 
-    let collision_count_5 = calculate_total_collisions(&board, &Slope { run: 1, rise: 2 });
+    writeln!(
+        writer,
+        "P6\n{} {}\n255",
+        image_width,
+        board.height() * scale
+    )?;
 
-    println!("Tree collisions for rise: 1, run: 1: {}", collision_count_1);
-    println!("Tree collisions for rise: 1, run: 3: {}", collision_count_2);
-    println!("Tree collisions for rise: 1, run: 5: {}", collision_count_3);
-    println!("Tree collisions for rise: 1, run: 7: {}", collision_count_4);
-    println!("Tree collisions for rise: 2, run: 1: {}", collision_count_5);
-    println!(
-        "Answer: {}",
-        collision_count_1
-            * collision_count_2
-            * collision_count_3
-            * collision_count_4
-            * collision_count_5
-    );
+    for y in 0..board.height() {
+        let pixels = overlay_row(board, slopes, width, y)
+            .flat_map(|cell| {
+                let color = match cell {
+                    (Cell::Tree, Some(index)) => {
+                        PATH_COLORS[index % PATH_COLORS.len()].map(|channel| channel / 2)
+                    }
+                    (Cell::Empty, Some(index)) => PATH_COLORS[index % PATH_COLORS.len()],
+                    (Cell::Tree, None) => TREE_COLOR,
+                    (Cell::Empty, None) => OPEN_COLOR,
+                };
+
+                std::iter::repeat_n(color, scale).flatten()
+            })
+            .collect::<Vec<_>>();
+
+        for _ in 0..scale {
+            writer.write_all(&pixels)?;
+        }
+    }
 
     Ok(())
 }
+
+/// Day 3: Toboggan Trajectory.
+pub struct TobogganTrajectory;
+
+impl Solution for TobogganTrajectory {
+    type Input = Board;
+    type Answer = u64;
+
+    fn parse(&self, input: &str) -> AppResult<Self::Input> {
+        Board::parse(input)
+    }
+
+    fn part1(&self, board: &Self::Input) -> AppResult<Self::Answer> {
+        calculate_total_collisions(board, &Slope { run: 3, rise: 1 })
+    }
+
+    fn part2(&self, board: &Self::Input) -> AppResult<Self::Answer> {
+        let slopes = [
+            Slope { run: 1, rise: 1 },
+            Slope { run: 3, rise: 1 },
+            Slope { run: 5, rise: 1 },
+            Slope { run: 7, rise: 1 },
+            Slope { run: 1, rise: 2 },
+        ];
+
+        slopes
+            .iter()
+            .map(|slope| calculate_total_collisions(board, slope))
+            .product()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
+
+    #[test]
+    fn part1_example() {
+        let board = TobogganTrajectory.parse(EXAMPLE).unwrap();
+
+        assert_eq!(TobogganTrajectory.part1(&board).unwrap(), 7);
+    }
+
+    #[test]
+    fn part2_example() {
+        let board = TobogganTrajectory.parse(EXAMPLE).unwrap();
+
+        assert_eq!(TobogganTrajectory.part2(&board).unwrap(), 336);
+    }
+
+    #[test]
+    fn search_example() {
+        let board = TobogganTrajectory.parse(EXAMPLE).unwrap();
+        let counts = search_slopes(&board, &Slope { run: 7, rise: 2 }).unwrap();
+        let trees = |run, rise| {
+            counts
+                .iter()
+                .find(|count| count.slope == Slope { run, rise })
+                .map(|count| count.trees)
+        };
+
+        assert_eq!(trees(3, 1), Some(7));
+        assert_eq!(trees(1, 2), Some(2));
+        // (2, 2) skips every other cell of (1, 1), so it's a slope of its own
+        assert_eq!(trees(2, 2), Some(1));
+        assert_eq!(trees(1, 1), Some(2));
+        assert!(counts.iter().all(|count| count.equivalent.is_empty()));
+        assert_eq!(counts.first().map(|count| count.trees), Some(0));
+        assert!(counts.windows(2).all(|pair| pair[0].trees <= pair[1].trees));
+    }
+
+    #[test]
+    fn runs_wrap_around_the_width() {
+        let board = TobogganTrajectory.parse("#..\n.#.\n..#").unwrap();
+        let counts = search_slopes(&board, &Slope { run: 4, rise: 1 }).unwrap();
+
+        assert_eq!(counts.len(), 3);
+        assert_eq!(
+            counts
+                .iter()
+                .find(|count| count.slope == Slope { run: 1, rise: 1 })
+                .map(|count| (count.trees, count.equivalent.clone())),
+            Some((2, vec![Slope { run: 4, rise: 1 }]))
+        );
+    }
+
+    #[test]
+    fn written_search() {
+        let board = TobogganTrajectory.parse("#..\n.#.\n..#").unwrap();
+        let mut output = vec![];
+
+        write_slope_search(&mut output, &board, &Slope { run: 4, rise: 1 }).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\
+Fewest (0 trees):
+  (0, 1), same as (3, 1)
+  (2, 1)
+Most (2 trees):
+  (1, 1), same as (4, 1)
+3 distinct of 5 slopes searched
+Slopes are the same only when they visit identical cells, so (2, 2), which skips every \
+other cell of (1, 1), is counted apart from it
+"
+        );
+    }
+
+    #[test]
+    fn ascii_path() {
+        let board = TobogganTrajectory.parse(EXAMPLE).unwrap();
+        let mut ascii = vec![];
+
+        render_ascii(&mut ascii, &board, &[Slope { run: 3, rise: 1 }]).unwrap();
+        let ascii = String::from_utf8(ascii).unwrap();
+        let lines = ascii.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 11);
+        assert!(lines.iter().all(|line| line.len() == 33));
+        assert_eq!(lines[0], "..##.........##.........##.......");
+        assert_eq!(lines[1], "#..O#...#..#...#...#..#...#...#..");
+        assert_eq!(lines[10], ".#..#...#.#.#..#...#.#.#..#...X.#");
+        assert_eq!(ascii.matches('X').count(), 7);
+    }
+
+    #[test]
+    fn wide_paths() {
+        let board = TobogganTrajectory.parse(EXAMPLE).unwrap();
+        let mut ascii = vec![];
+
+        render_ascii(&mut ascii, &board, &[Slope { run: 1000, rise: 1 }]).unwrap();
+        let ascii = String::from_utf8(ascii).unwrap();
+        let lines = ascii.lines().collect::<Vec<_>>();
+
+        assert!(lines.iter().all(|line| line.len() == 910 * 11));
+        assert_eq!(lines[10].find(['X', 'O']), Some(10000));
+        assert_eq!(ascii.matches(['X', 'O']).count(), 10);
+
+        assert!(render_ascii(
+            vec![],
+            &board,
+            &[Slope {
+                run: usize::MAX,
+                rise: 1
+            }]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn ppm_header_and_size() {
+        let board = TobogganTrajectory.parse(EXAMPLE).unwrap();
+        let slopes = [Slope { run: 1, rise: 1 }, Slope { run: 1, rise: 2 }];
+        let mut ppm = vec![];
+
+        render_ppm(&mut ppm, &board, &slopes, 2).unwrap();
+
+        let header = b"P6\n22 22\n255\n";
+        assert!(ppm.starts_with(header));
+        assert_eq!(ppm.len(), header.len() + 22 * 22 * 3);
+    }
+
+    #[test]
+    fn flat_slopes() {
+        let board = TobogganTrajectory.parse(EXAMPLE).unwrap();
+        let flat = Slope { run: 3, rise: 0 };
+
+        assert!(calculate_total_collisions(&board, &flat).is_err());
+        assert!(search_slopes(&board, &flat).is_err());
+        assert!(render_ascii(vec![], &board, &[flat]).is_err());
+    }
+
+    #[test]
+    fn ragged_rows() {
+        assert!(TobogganTrajectory.parse("..#\n.#").is_err());
+    }
+}
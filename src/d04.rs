@@ -1,153 +1,639 @@
-use regex::Regex;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::io::{self, Write};
 
 use crate::errors::AppResult;
+use crate::records::{Fields, Record, RecordReader};
+use crate::report::csv_field;
+use crate::schema::{self, FieldSpec, Schema, Violation};
+use crate::solution::Solution;
 
 lazy_static! {
-    static ref HEIGHT_REGEX: Regex = Regex::new(r"^(\d+)(in|cm)$").unwrap();
-    static ref HAIR_COLOR_REGEX: Regex = Regex::new(r"^#(?:\d|[a-f]){6}$").unwrap();
-    static ref ID_REGEX: Regex = Regex::new(r"^\d{9}$").unwrap();
-    static ref EYE_COLORS: [&'static str; 7] = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+    static ref DEFAULT_SCHEMA: Schema = default_schema();
 }
 
-const INPUT: &'static str = include_str!("../data/passports.txt");
+/// The passport rules of the puzzle, also written out in
+/// `passport-schema.toml`.
+pub fn default_schema() -> Schema {
+    Schema::builder()
+        .field(FieldSpec::required("byr").range(1920, 2002))
+        .field(FieldSpec::required("iyr").range(2010, 2020))
+        .field(FieldSpec::required("eyr").range(2020, 2030))
+        .field(
+            FieldSpec::required("hgt")
+                .unit("cm", 150, 193)
+                .unit("in", 59, 76),
+        )
+        .field(FieldSpec::required("hcl").pattern("#[0-9a-f]{6}"))
+        .field(
+            FieldSpec::required("ecl").one_of(&["amb", "blu", "brn", "gry", "grn", "hzl", "oth"]),
+        )
+        .field(FieldSpec::required("pid").digits(9))
+        .field(FieldSpec::optional("cid"))
+        .build()
+        .expect("Invalid default passport schema")
+}
 
-#[derive(Debug)]
-struct Height {
-    value: u32,
-    unit: String,
+/// A height with one of the units of the schema's `hgt` field, e.g. `cm`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Height {
+    pub value: u32,
+    pub unit: String,
 }
 
-#[derive(Debug)]
-struct Passport {
-    id: String,
-    country_id: Option<String>,
-    birth_year: u32,
-    issue_year: u32,
-    expiration_year: u32,
-    height: Height,
-    hair_color: String,
-    eye_color: String,
+fn parse_year(fields: &Fields, key: &str) -> Option<u32> {
+    fields.get(key)?.parse().ok()
 }
 
-impl Passport {
-    pub fn is_valid(&self) -> bool {
-        if !(self.birth_year >= 1920 && self.birth_year <= 2002) {
-            return false;
-        }
+fn parse_height(fields: &Fields, schema: &Schema) -> Option<Height> {
+    let (value, unit) = schema.split_unit("hgt", fields.get("hgt")?)?;
 
-        if !(self.issue_year >= 2010 && self.issue_year <= 2020) {
-            return false;
-        }
+    Some(Height {
+        value: value.parse().ok()?,
+        unit: unit.to_string(),
+    })
+}
+
+/// Reads every passport of a batch file, see [`RecordReader`] for the
+/// format and [`RecordReader::default`] for its policies.
+pub fn parse_records(input: &str) -> AppResult<Vec<Record>> {
+    RecordReader::new().read(input)
+}
+
+/// How much of a passport is checked: only that the required fields are
+/// present, or also that every field present passes its rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Validation {
+    RequiredFields,
+    EveryRule,
+}
+
+/// Counts the passports with every field required by `schema` present,
+/// whatever their values.
+pub fn count_present(records: &[Record], schema: &Schema) -> usize {
+    records
+        .iter()
+        .filter(|record| schema.has_required(&record.fields))
+        .count()
+}
+
+/// Counts the passports valid under `schema`.
+pub fn count_valid(records: &[Record], schema: &Schema) -> usize {
+    records
+        .iter()
+        .filter(|record| schema.is_valid(&record.fields))
+        .count()
+}
+
+/// Every rule each passport breaks under `schema`, only counting missing
+/// fields when `validation` is [`Validation::RequiredFields`]. See
+/// [`Schema::validate`].
+pub fn violations(
+    records: &[Record],
+    schema: &Schema,
+    validation: Validation,
+) -> Vec<Vec<Violation>> {
+    records
+        .iter()
+        .map(|record| match validation {
+            Validation::RequiredFields => schema.missing(&record.fields),
+            Validation::EveryRule => schema.validate(&record.fields),
+        })
+        .collect()
+}
 
-        if !(self.expiration_year >= 2020 && self.expiration_year <= 2030) {
-            return false;
+/// Writes how many `records` have every field required by `schema` and how
+/// many are valid, whatever `validation` is. These are preceded by every rule
+/// checked that each passport breaks when `explain` is set, and by how many
+/// passports each rule rejects when `summary` is set.
+pub fn write_report<W: Write>(
+    mut writer: W,
+    records: &[Record],
+    schema: &Schema,
+    validation: Validation,
+    explain: bool,
+    summary: bool,
+) -> AppResult<()> {
+    let violations = violations(records, schema, validation);
+
+    if explain {
+        for (record, violations) in records.iter().zip(&violations) {
+            if violations.is_empty() {
+                continue;
+            }
+
+            writeln!(writer, "Passport {} ({}):", record.number, record.span)?;
+            for violation in violations {
+                writeln!(writer, "  {}", violation)?;
+            }
         }
+    }
 
-        if self.height.unit == "cm" && !(self.height.value >= 150 && self.height.value <= 193) {
-            return false;
+    if summary {
+        writeln!(writer, "Rules rejecting the most passports:")?;
+        for count in schema::count_rejections(violations.iter().map(Vec::as_slice)) {
+            writeln!(
+                writer,
+                "  {:>5}  {} {}",
+                count.records, count.key, count.rule
+            )?;
         }
+    }
 
-        if self.height.unit == "in" && !(self.height.value >= 59 && self.height.value <= 76) {
-            return false;
+    writeln!(
+        writer,
+        "{} of {} passports have every required field",
+        count_present(records, schema),
+        records.len()
+    )?;
+    writeln!(
+        writer,
+        "{} of {} passports are valid",
+        count_valid(records, schema),
+        records.len()
+    )?;
+
+    Ok(())
+}
+
+/// The fields of a passport as exported, typed where they're in the expected
+/// format and `None` where they're missing or aren't, along with the verdict
+/// of a schema on it. The height is split on the units of the schema.
+#[derive(Debug, Serialize)]
+pub struct Export<'a> {
+    pub number: usize,
+    pub first_line: usize,
+    pub last_line: usize,
+    pub id: Option<&'a str>,
+    pub country_id: Option<&'a str>,
+    pub birth_year: Option<u32>,
+    pub issue_year: Option<u32>,
+    pub expiration_year: Option<u32>,
+    pub height: Option<Height>,
+    pub hair_color: Option<&'a str>,
+    pub eye_color: Option<&'a str>,
+    pub valid: bool,
+    /// Every rule broken, e.g. `hgt: 190in outside 59..=76`.
+    pub failing_rules: Vec<String>,
+}
+
+impl<'a> Export<'a> {
+    pub fn new(record: &'a Record, schema: &Schema) -> Self {
+        let fields = &record.fields;
+        let text = |key: &str| fields.get(key).map(String::as_str);
+        let failing_rules = schema
+            .validate(fields)
+            .iter()
+            .map(|violation| violation.to_string())
+            .collect::<Vec<_>>();
+
+        Export {
+            number: record.number,
+            first_line: record.span.start,
+            last_line: record.span.end,
+            id: text("pid"),
+            country_id: text("cid"),
+            birth_year: parse_year(fields, "byr"),
+            issue_year: parse_year(fields, "iyr"),
+            expiration_year: parse_year(fields, "eyr"),
+            height: parse_height(fields, schema),
+            hair_color: text("hcl"),
+            eye_color: text("ecl"),
+            valid: failing_rules.is_empty(),
+            failing_rules,
         }
+    }
+}
 
-        if !(EYE_COLORS.contains(&self.eye_color.as_str())) {
-            return false;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// CSV with a header row, the height split into `height_value` and
+    /// `height_unit`, and the failing rules joined by `; `.
+    Csv,
+    /// A JSON object per line.
+    JsonLines,
+}
+
+const CSV_HEADER: &str = "number,first_line,last_line,id,country_id,birth_year,issue_year,\
+expiration_year,height_value,height_unit,hair_color,eye_color,valid,failing_rules";
+
+fn csv_option<T: ToString>(value: Option<T>) -> String {
+    value
+        .map(|value| csv_field(&value.to_string()))
+        .unwrap_or_default()
+}
+
+/// Writes exported passports one at a time.
+pub struct ExportWriter<W: Write> {
+    writer: W,
+    format: ExportFormat,
+}
+
+impl<W: Write> ExportWriter<W> {
+    pub fn new(mut writer: W, format: ExportFormat) -> io::Result<Self> {
+        if format == ExportFormat::Csv {
+            writeln!(writer, "{}", CSV_HEADER)?;
         }
 
-        if !(HAIR_COLOR_REGEX.is_match(&self.hair_color)) {
-            return false;
+        Ok(ExportWriter { writer, format })
+    }
+
+    pub fn write(&mut self, export: &Export) -> io::Result<()> {
+        match self.format {
+            ExportFormat::Csv => writeln!(
+                self.writer,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                export.number,
+                export.first_line,
+                export.last_line,
+                csv_option(export.id),
+                csv_option(export.country_id),
+                csv_option(export.birth_year),
+                csv_option(export.issue_year),
+                csv_option(export.expiration_year),
+                csv_option(export.height.as_ref().map(|height| height.value)),
+                csv_option(export.height.as_ref().map(|height| &height.unit)),
+                csv_option(export.hair_color),
+                csv_option(export.eye_color),
+                export.valid,
+                csv_field(&export.failing_rules.join("; "))
+            ),
+            ExportFormat::JsonLines => {
+                serde_json::to_writer(&mut self.writer, export)?;
+                writeln!(self.writer)
+            }
         }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Writes every passport of `records` with its typed fields and the verdict
+/// of `schema` on it, in `format`.
+pub fn write_export<W: Write>(
+    writer: W,
+    records: &[Record],
+    schema: &Schema,
+    format: ExportFormat,
+) -> AppResult<()> {
+    let mut writer = ExportWriter::new(writer, format)?;
+    for record in records {
+        writer.write(&Export::new(record, schema))?;
+    }
+
+    Ok(writer.flush()?)
+}
+
+/// Day 4: Passport Processing.
+pub struct PassportProcessing;
+
+impl Solution for PassportProcessing {
+    type Input = Vec<Record>;
+    type Answer = usize;
+
+    fn parse(&self, input: &str) -> AppResult<Self::Input> {
+        parse_records(input)
+    }
+
+    fn part1(&self, passports: &Self::Input) -> AppResult<Self::Answer> {
+        Ok(count_present(passports, &DEFAULT_SCHEMA))
+    }
+
+    fn part2(&self, passports: &Self::Input) -> AppResult<Self::Answer> {
+        Ok(count_valid(passports, &DEFAULT_SCHEMA))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{count_rejections, RuleKind};
+
+    const INVALID: &str = "\
+eyr:1972 cid:100
+hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+
+iyr:2019
+hcl:#602927 eyr:1967 hgt:170cm
+ecl:grn pid:012533040 byr:1946
 
-        if !(ID_REGEX.is_match(&self.id)) {
-            return false;
+hcl:dab227 iyr:2012
+ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277
+
+hgt:59cm ecl:zzz
+eyr:2038 hcl:74454a iyr:2023
+pid:3556412378 byr:2007";
+
+    const VALID: &str = "\
+pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+eyr:2029 ecl:blu cid:129 byr:1989
+iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm
+
+hcl:#888785
+hgt:164cm byr:2001 iyr:2015 cid:88
+pid:545766238 ecl:hzl
+eyr:2022
+
+iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
+
+    #[test]
+    fn part1_example() {
+        let passports = PassportProcessing
+            .parse(
+                "\
+ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929
+
+hcl:#ae17e1 iyr:2013
+eyr:2024
+ecl:brn pid:760753108 byr:1931
+hgt:179cm
+
+hcl:#cfa07d eyr:2025 pid:166559648
+iyr:2011 ecl:brn hgt:59in",
+            )
+            .unwrap();
+
+        assert_eq!(PassportProcessing.part1(&passports).unwrap(), 2);
+    }
+
+    #[test]
+    fn part2_invalid_examples() {
+        let passports = PassportProcessing.parse(INVALID).unwrap();
+
+        assert_eq!(PassportProcessing.part2(&passports).unwrap(), 0);
+    }
+
+    #[test]
+    fn part2_valid_examples() {
+        let passports = PassportProcessing.parse(VALID).unwrap();
+
+        assert_eq!(PassportProcessing.part2(&passports).unwrap(), 4);
+    }
+
+    #[test]
+    fn missing_fields_are_invalid() {
+        let passports = PassportProcessing
+            .parse("hcl:#cfa07d eyr:2025 pid:166559648\niyr:2011 ecl:brn hgt:59in")
+            .unwrap();
+
+        assert_eq!(PassportProcessing.part2(&passports).unwrap(), 0);
+    }
+
+    #[test]
+    fn malformed_values_are_invalid() {
+        let passports = PassportProcessing
+            .parse("byr:abc iyr:2015 eyr:2025 hgt:170cm hcl:#123abc ecl:brn pid:000000001")
+            .unwrap();
+
+        assert_eq!(PassportProcessing.part1(&passports).unwrap(), 1);
+        assert_eq!(PassportProcessing.part2(&passports).unwrap(), 0);
+        assert!(violations(&passports, &DEFAULT_SCHEMA, Validation::RequiredFields)[0].is_empty());
+    }
+
+    #[test]
+    fn schema_file_matches_default() {
+        let schema = Schema::from_toml(include_str!("../passport-schema.toml")).unwrap();
+        let valid = parse_records(VALID).unwrap();
+        let invalid = parse_records(INVALID).unwrap();
+
+        assert_eq!(count_valid(&valid, &schema), 4);
+        assert_eq!(count_valid(&invalid, &schema), 0);
+    }
+
+    #[test]
+    fn custom_json_schema() {
+        let schema = Schema::from_json(
+            r#"{"fields": [
+                {"key": "pid", "pattern": "[0-9]{9}"},
+                {"key": "hgt", "required": false, "units": {"m": {"min": 1, "max": 3}}},
+                {"key": "ecl", "one-of": ["grn", "blu"]}
+            ]}"#,
+        )
+        .unwrap();
+        let records = parse_records(
+            "pid:087499704 ecl:grn\n\npid:087499704 ecl:grn hgt:74in\n\npid:0874 ecl:blu",
+        )
+        .unwrap();
+
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| schema.is_valid(&record.fields))
+                .collect::<Vec<_>>(),
+            vec![true, false, false]
+        );
+    }
+
+    #[test]
+    fn every_violation_is_listed() {
+        let records = parse_records(
+            "byr:1919 iyr:2015 eyr:2025 hgt:190in hcl:123abc pid:0123456789\n\n\
+             byr:abc hgt:190 ecl:zzz pid:12345678a",
+        )
+        .unwrap();
+        let messages = violations(&records, &DEFAULT_SCHEMA, Validation::EveryRule)
+            .iter()
+            .map(|violations| {
+                violations
+                    .iter()
+                    .map(|violation| violation.to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            messages[0],
+            vec![
+                "byr: 1919 outside 1920..=2002",
+                "hgt: 190in outside 59..=76",
+                "hcl: \"123abc\" does not match #[0-9a-f]{6}",
+                "ecl: missing",
+                "pid: 10 digits, expected 9",
+            ]
+        );
+        assert_eq!(
+            messages[1],
+            vec![
+                "byr: \"abc\" is not a number",
+                "iyr: missing",
+                "eyr: missing",
+                "hgt: \"190\" has no unit, expected cm or in",
+                "hcl: missing",
+                "ecl: \"zzz\" is not one of amb, blu, brn, gry, grn, hzl, oth",
+                "pid: \"12345678a\" is not a number",
+            ]
+        );
+    }
+
+    #[test]
+    fn rules_rejecting_the_most() {
+        let records = parse_records(INVALID).unwrap();
+        let violations = violations(&records, &DEFAULT_SCHEMA, Validation::EveryRule);
+        let counts = count_rejections(violations.iter().map(Vec::as_slice));
+
+        let top = counts
+            .iter()
+            .take(4)
+            .map(|count| (count.key.as_str(), count.rule, count.records))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            top,
+            vec![
+                ("eyr", RuleKind::Range, 3),
+                ("hcl", RuleKind::Pattern, 2),
+                ("hgt", RuleKind::Units, 2),
+                ("pid", RuleKind::Digits, 2),
+            ]
+        );
+        assert!(counts[4..].iter().all(|count| count.records == 1));
+    }
+
+    #[test]
+    fn written_report() {
+        let records =
+            parse_records("byr:1937 hgt:190in\n\n\n\n\n\n\n\npid:1 cid:9\nbyr:1900").unwrap();
+        let mut output = vec![];
+
+        write_report(
+            &mut output,
+            &records,
+            &DEFAULT_SCHEMA,
+            Validation::RequiredFields,
+            true,
+            true,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.starts_with("Passport 1 (line 1):\n  iyr: missing\n"));
+        assert!(output.contains("Passport 2 (lines 9-10):\n"));
+        assert!(output.contains("Rules rejecting the most passports:\n      2  ecl missing\n"));
+        assert!(output
+            .ends_with("0 of 2 passports have every required field\n0 of 2 passports are valid\n"));
+    }
+
+    #[test]
+    fn export_csv() {
+        let records = parse_records(
+            "byr:1937 hgt:190in pid:0,1\n\n\
+             ecl:amb byr:1980 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f pid:087499704 cid:9",
+        )
+        .unwrap();
+        let mut csv = vec![];
+
+        let mut writer = ExportWriter::new(&mut csv, ExportFormat::Csv).unwrap();
+        for record in &records {
+            writer.write(&Export::new(record, &DEFAULT_SCHEMA)).unwrap();
         }
 
-        true
-    }
-}
-
-pub fn run() -> AppResult<()> {
-    let passports = INPUT
-        .split("\n\n")
-        .filter_map(|line| {
-            let passport_parts = line
-                .split_whitespace()
-                .map(|part| {
-                    let property_parts = part.split(":").collect::<Vec<_>>();
-                    (
-                        *property_parts.get(0).expect("Malformed passport"),
-                        *property_parts.get(1).expect("Malformed passport"),
-                    )
-                })
-                .collect::<HashMap<_, _>>();
-
-            let id = match passport_parts.get("pid") {
-                Some(id) => id.to_string(),
-                None => return None,
-            };
-
-            let country_id = passport_parts.get("cid").map(|id| id.to_string());
-
-            let birth_year = match passport_parts.get("byr") {
-                Some(id) => id.parse::<u32>().expect("Malformed birth year"),
-                None => return None,
-            };
-
-            let issue_year = match passport_parts.get("iyr") {
-                Some(id) => id.parse::<u32>().expect("Malformed issue year"),
-                None => return None,
-            };
-
-            let expiration_year = match passport_parts.get("eyr") {
-                Some(id) => id.parse::<u32>().expect("Malformed expiration year"),
-                None => return None,
-            };
-
-            let height = match passport_parts.get("hgt") {
-                Some(&id) => {
-                    let captures = HEIGHT_REGEX.captures(id)?;
-                    let value = captures
-                        .get(1)?
-                        .as_str()
-                        .parse::<u32>()
-                        .expect("Malformed height");
-                    let unit = captures.get(2)?.as_str().to_string();
-
-                    Height { value, unit }
-                }
-                None => return None,
-            };
-
-            let hair_color = match passport_parts.get("hcl") {
-                Some(id) => id.to_string(),
-                None => return None,
-            };
-
-            let eye_color = match passport_parts.get("ecl") {
-                Some(id) => id.to_string(),
-                None => return None,
-            };
-
-            let passport = Passport {
-                id,
-                country_id,
-                birth_year,
-                issue_year,
-                expiration_year,
-                height,
-                hair_color,
-                eye_color,
-            };
-
-            match passport.is_valid() {
-                true => Some(passport),
-                false => None,
-            }
-        })
-        .collect::<Vec<_>>();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
 
-    println!("{:?}", passports.len());
-    Ok(())
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(
+            lines[1],
+            "1,1,1,\"0,1\",,1937,,,190,in,,,false,\"iyr: missing; eyr: missing; \
+             hgt: 190in outside 59..=76; hcl: missing; ecl: missing; pid: \"\"0,1\"\" is not a number\""
+        );
+        assert_eq!(
+            lines[2],
+            "2,3,3,087499704,9,1980,2012,2030,74,in,#623a2f,amb,true,"
+        );
+    }
+
+    #[test]
+    fn export_json() {
+        let records = parse_records("byr:abc hgt:170cm").unwrap();
+        let mut json = vec![];
+
+        ExportWriter::new(&mut json, ExportFormat::JsonLines)
+            .unwrap()
+            .write(&Export::new(&records[0], &DEFAULT_SCHEMA))
+            .unwrap();
+
+        let json = serde_json::from_slice::<serde_json::Value>(&json).unwrap();
+        assert_eq!(json["birth_year"], serde_json::Value::Null);
+        assert_eq!(json["height"]["value"], 170);
+        assert_eq!(json["height"]["unit"], "cm");
+        assert_eq!(json["country_id"], serde_json::Value::Null);
+        assert_eq!(json["valid"], false);
+        assert_eq!(json["failing_rules"][0], "byr: \"abc\" is not a number");
+    }
+
+    #[test]
+    fn export_height_units() {
+        let schema = Schema::builder()
+            .field(
+                FieldSpec::required("hgt")
+                    .unit("m", 1, 3)
+                    .unit("mm", 1000, 3000),
+            )
+            .build()
+            .unwrap();
+        let height = |value: &str, schema: &Schema| {
+            let records = parse_records(&format!("hgt:{}", value)).unwrap();
+
+            Export::new(&records[0], schema).height
+        };
+
+        assert_eq!(
+            height("1800mm", &schema),
+            Some(Height {
+                value: 1800,
+                unit: "mm".to_string()
+            })
+        );
+        assert_eq!(height("170cm", &schema), None);
+        assert_eq!(
+            height("170cm", &DEFAULT_SCHEMA).map(|height| height.unit),
+            Some("cm".to_string())
+        );
+        assert_eq!(height("cm", &DEFAULT_SCHEMA), None);
+    }
+
+    #[test]
+    fn longest_unit_wins() {
+        let schema = Schema::builder()
+            .field(
+                FieldSpec::required("dur")
+                    .unit("in", 1, 10)
+                    .unit("min", 1, 10),
+            )
+            .build()
+            .unwrap();
+        let fields = |value: &str| {
+            vec![("dur".to_string(), value.to_string())]
+                .into_iter()
+                .collect::<Fields>()
+        };
+
+        assert!(schema.is_valid(&fields("5min")));
+        assert!(schema.is_valid(&fields("5in")));
+        assert_eq!(
+            schema.validate(&fields("50min"))[0].to_string(),
+            "dur: 50min outside 1..=10"
+        );
+    }
+
+    #[test]
+    fn invalid_schemas() {
+        assert!(Schema::builder()
+            .field(FieldSpec::required("pid").pattern("[0-9"))
+            .build()
+            .is_err());
+        assert!(Schema::builder()
+            .field(FieldSpec::required("pid"))
+            .field(FieldSpec::optional("pid"))
+            .build()
+            .is_err());
+        assert!(Schema::from_toml("[[fields]]\nkey = \"pid\"\nlength = 9").is_err());
+    }
 }
@@ -1,12 +1,12 @@
-use crate::errors::AppResult;
-
-const INPUT: &'static str = include_str!("../data/boarding-passes.txt");
+use crate::errors::{AppError, AppResult};
+use crate::solution::Solution;
 
+/// A seat on the plane. Seats are ordered by their id.
 #[derive(Debug, Eq)]
-struct Seat {
-    row: u8,
-    column: u8,
-    id: u32,
+pub struct Seat {
+    pub row: u8,
+    pub column: u8,
+    pub id: u32,
 }
 
 impl Seat {
@@ -37,60 +37,129 @@ impl PartialEq for Seat {
     }
 }
 
-fn calculate_location(length: u8, partitions: &[&str]) -> u8 {
-    let range = partitions
-        .iter()
-        .fold(0..(length - 1), |acc, &direction| match direction {
-            "F" | "L" => acc.start..((acc.end + acc.start) / 2),
-            "B" | "R" => (((acc.end + acc.start) / 2) + 1)..acc.end,
-            _ => panic!(format!("Unknown direction \"{}\"", direction)),
-        });
+fn calculate_location(
+    line_number: usize,
+    column: usize,
+    length: u8,
+    partitions: &str,
+) -> AppResult<u8> {
+    let range =
+        partitions
+            .chars()
+            .enumerate()
+            .try_fold(0..(length - 1), |acc, (index, direction)| match direction {
+                'F' | 'L' => Ok(acc.start..((acc.end + acc.start) / 2)),
+                'B' | 'R' => Ok((((acc.end + acc.start) / 2) + 1)..acc.end),
+                _ => Err(AppError::parse(
+                    line_number,
+                    column + index,
+                    &format!("Unknown direction \"{}\"", direction),
+                )),
+            })?;
 
     if range.start != range.end {
-        panic!("Invalid partition size");
+        return Err(AppError::parse(
+            line_number,
+            column,
+            "Invalid partition size",
+        ));
+    }
+
+    Ok(range.start)
+}
+
+/// Decodes a boarding pass like `FBFBBFFRLR` into its seat.
+pub fn parse_boarding_pass(line_number: usize, line: &str) -> AppResult<Seat> {
+    if line.len() != 10 || !line.is_ascii() {
+        return Err(AppError::parse(
+            line_number,
+            1,
+            &format!("Expected 10 character boarding pass, found \"{}\"", line),
+        ));
     }
 
-    range.start
+    Ok(Seat::new(
+        calculate_location(line_number, 1, 128, &line[..7])?,
+        calculate_location(line_number, 8, 8, &line[7..])?,
+    ))
 }
 
-pub fn run() -> AppResult<()> {
-    let mut seats = INPUT
-        .split("\n")
-        .map(|line| {
-            let row_partition = line[..7].split_terminator("").skip(1).collect::<Vec<_>>();
-            let column_partition = line[7..].split_terminator("").skip(1).collect::<Vec<_>>();
-
-            Seat::new(
-                calculate_location(128, &row_partition),
-                calculate_location(8, &column_partition),
-            )
-        })
-        .collect::<Vec<_>>();
-
-    seats.sort();
-
-    println!("Largest seat ID: \"{}\"", seats.last().unwrap().id);
-
-    let seat = seats
-        .iter()
-        .skip(1)
-        .map(|x| x.id)
-        .collect::<Vec<_>>()
-        .chunks(3)
-        .find_map(|chunk| {
-            let first = chunk[0];
-            let second = chunk[1];
-            let third = chunk[2];
-
-            match (first + 1 == second, third - 1 == second) {
-                (false, true) => Some(first + 1),
-                (true, false) => Some(third - 1),
-                (true, true) | (false, false) => None,
-            }
-        })
-        .unwrap();
-
-    println!("Seat ID: \"{}\"", seat);
-
-    Ok(())
+/// Day 5: Binary Boarding.
+pub struct BinaryBoarding;
+
+impl Solution for BinaryBoarding {
+    type Input = Vec<Seat>;
+    type Answer = u32;
+
+    fn parse(&self, input: &str) -> AppResult<Self::Input> {
+        let mut seats = input
+            .split('\n')
+            .enumerate()
+            .map(|(index, line)| parse_boarding_pass(index + 1, line))
+            .collect::<AppResult<Vec<_>>>()?;
+
+        seats.sort();
+
+        Ok(seats)
+    }
+
+    fn part1(&self, seats: &Self::Input) -> AppResult<Self::Answer> {
+        seats
+            .last()
+            .map(|seat| seat.id)
+            .ok_or_else(|| AppError::invalid_input("No boarding passes"))
+    }
+
+    fn part2(&self, seats: &Self::Input) -> AppResult<Self::Answer> {
+        // our seat is the only gap, so the seats either side of it are taken
+        seats
+            .windows(2)
+            .find_map(|pair| match pair[1].id - pair[0].id {
+                2 => Some(pair[0].id + 1),
+                _ => None,
+            })
+            .ok_or_else(|| AppError::unsolvable("Could not find an empty seat"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boarding_pass_examples() {
+        let seats = BinaryBoarding
+            .parse("FBFBBFFRLR\nBFFFBBFRRR\nFFFBBBFRRR\nBBFFBBFRLL")
+            .unwrap();
+
+        assert_eq!(
+            seats,
+            vec![
+                Seat::new(14, 7),
+                Seat::new(44, 5),
+                Seat::new(70, 7),
+                Seat::new(102, 4),
+            ]
+        );
+        assert_eq!(
+            seats.iter().map(|seat| seat.id).collect::<Vec<_>>(),
+            vec![119, 357, 567, 820]
+        );
+        assert_eq!(BinaryBoarding.part1(&seats).unwrap(), 820);
+    }
+
+    #[test]
+    fn part2_finds_the_gap() {
+        let seats = (0..8)
+            .filter(|&column| column != 3)
+            .map(|column| Seat::new(1, column))
+            .collect::<Vec<_>>();
+
+        assert_eq!(BinaryBoarding.part2(&seats).unwrap(), 11);
+    }
+
+    #[test]
+    fn unknown_direction() {
+        assert!(BinaryBoarding.parse("FBFBBFFRLX").is_err());
+    }
 }
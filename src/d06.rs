@@ -1,43 +1,78 @@
 use std::collections::HashMap;
 
 use crate::errors::AppResult;
+use crate::solution::Solution;
 
-const INPUT: &'static str = include_str!("../data/customs-answers.txt");
-
-pub fn run() -> AppResult<()> {
-    let answer1 = INPUT
-        .split("\n\n")
-        .map(|group| {
-            let group = group.replace("\n", "");
-            let mut answers = group.split_terminator("").skip(1).collect::<Vec<_>>();
-            answers.sort();
-            answers.dedup();
-            answers.len()
-        })
-        .fold(0, |acc, x| acc + x);
-
-    println!("Answer 1: \"{}\"", answer1);
-
-    let answer2 = INPUT
-        .split("\n\n")
-        .map(|group| {
-            let people_count = group.split("\n").count();
-            let answer_totals = group.split("\n").fold(HashMap::new(), |mut acc, x| {
-                for ch in x.chars() {
-                    *acc.entry(ch).or_insert(0) += 1;
-                }
-
-                acc
-            });
-
-            answer_totals
-                .iter()
-                .filter(|(_, &count)| count == people_count)
-                .count()
-        })
-        .fold(0, |acc, x| acc + x);
-
-    println!("Answer 2: \"{}\"", answer2);
-
-    Ok(())
+/// Counts the questions anyone in `group` answered "yes" to. Each line of
+/// the group is one person's answers.
+pub fn count_anyone_answered(group: &str) -> usize {
+    let group = group.replace('\n', "");
+    let mut answers = group.split_terminator("").skip(1).collect::<Vec<_>>();
+    answers.sort();
+    answers.dedup();
+    answers.len()
+}
+
+/// Counts the questions everyone in `group` answered "yes" to.
+pub fn count_everyone_answered(group: &str) -> usize {
+    let people_count = group.split('\n').count();
+    let answer_totals = group.split('\n').fold(HashMap::new(), |mut acc, x| {
+        for ch in x.chars() {
+            *acc.entry(ch).or_insert(0) += 1;
+        }
+
+        acc
+    });
+
+    answer_totals
+        .iter()
+        .filter(|(_, &count)| count == people_count)
+        .count()
+}
+
+/// Day 6: Custom Customs.
+pub struct CustomCustoms;
+
+impl Solution for CustomCustoms {
+    type Input = Vec<String>;
+    type Answer = usize;
+
+    fn parse(&self, input: &str) -> AppResult<Self::Input> {
+        Ok(input.split("\n\n").map(str::to_string).collect())
+    }
+
+    fn part1(&self, groups: &Self::Input) -> AppResult<Self::Answer> {
+        Ok(groups
+            .iter()
+            .map(|group| count_anyone_answered(group))
+            .sum::<usize>())
+    }
+
+    fn part2(&self, groups: &Self::Input) -> AppResult<Self::Answer> {
+        Ok(groups
+            .iter()
+            .map(|group| count_everyone_answered(group))
+            .sum::<usize>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb";
+
+    #[test]
+    fn part1_example() {
+        let groups = CustomCustoms.parse(EXAMPLE).unwrap();
+
+        assert_eq!(CustomCustoms.part1(&groups).unwrap(), 11);
+    }
+
+    #[test]
+    fn part2_example() {
+        let groups = CustomCustoms.parse(EXAMPLE).unwrap();
+
+        assert_eq!(CustomCustoms.part2(&groups).unwrap(), 6);
+    }
 }
@@ -5,11 +5,12 @@ use std::{
     str::CharIndices,
 };
 
-use crate::errors::AppResult;
+use crate::errors::{AppResult, ParseError};
+use crate::solution::Solution;
 
-const INPUT: &'static str = include_str!("../data/bag-rules.txt");
+/// A token of a bag rule. Identifers borrow from the input.
 #[derive(Debug)]
-enum Token<'a> {
+pub enum Token<'a> {
     Identifer(&'a str),
     Integer(i32),
     Unknown(&'a str),
@@ -23,7 +24,10 @@ enum Token<'a> {
 // bag_count = <NUMBER> <name>
 // empty_bag = no other bags
 // <name> contain <<bag_count>[, ...bag_count]|<empty_bag>>.
-struct Lexer<'a> {
+
+/// Splits bag rules into tokens, each paired with its byte offset into the
+/// input.
+pub struct Lexer<'a> {
     input: &'a str,
     iter: Peekable<CharIndices<'a>>,
     position: usize,
@@ -69,11 +73,12 @@ impl<'a> Lexer<'a> {
     fn consume_integer(&mut self, position: usize) -> Token<'a> {
         self.position = position;
         self.offset = self.consume_while(char::is_numeric);
-        let value = self.input[self.position..=self.offset]
-            .parse::<i32>()
-            .expect("Unable to parse number as int");
+        let value = &self.input[self.position..=self.offset];
 
-        Token::Integer(value)
+        match value.parse::<i32>() {
+            Ok(value) => Token::Integer(value),
+            Err(_) => Token::Unknown(value),
+        }
     }
 
     fn consume_comma(&mut self, position: usize) -> Token<'a> {
@@ -92,7 +97,7 @@ impl<'a> Lexer<'a> {
 }
 
 impl<'a> Iterator for Lexer<'a> {
-    type Item = Token<'a>;
+    type Item = (usize, Token<'a>);
 
     fn next(&mut self) -> Option<Self::Item> {
         match self.iter.next() {
@@ -102,30 +107,45 @@ impl<'a> Iterator for Lexer<'a> {
 
                 self.next()
             }
-            Some((position, c)) if c.is_alphabetic() => Some(self.consume_identifier(position)),
-            Some((position, c)) if c.is_numeric() => Some(self.consume_integer(position)),
-            Some((position, ',')) => Some(self.consume_comma(position)),
-            Some((position, '.')) => Some(self.consume_dot(position)),
-            Some((index, _)) => Some(Token::Unknown(&self.input[index..])),
+            Some((position, c)) if c.is_alphabetic() => {
+                Some((position, self.consume_identifier(position)))
+            }
+            Some((position, c)) if c.is_numeric() => {
+                Some((position, self.consume_integer(position)))
+            }
+            Some((position, ',')) => Some((position, self.consume_comma(position))),
+            Some((position, '.')) => Some((position, self.consume_dot(position))),
+            Some((index, _)) => Some((index, Token::Unknown(&self.input[index..]))),
             None => None,
         }
     }
 }
 
+// byte offset into the input and a description of what went wrong
+type ParseResult<T> = Result<T, (usize, String)>;
+
+fn unexpected(token: Option<(usize, Token)>, end: usize) -> (usize, String) {
+    match token {
+        Some((position, token)) => (position, format!("Unexpected token \"{:?}\"", token)),
+        None => (end, "Unexpected end of input".to_string()),
+    }
+}
+
+/// A bag colour and how many of them a rule requires.
 #[derive(Debug, PartialEq, Clone)]
-struct Bag {
-    name: String,
-    count: i32,
+pub struct Bag {
+    pub name: String,
+    pub count: i32,
 }
 
 fn parse_bag(lexer: &mut Peekable<Lexer>) -> Bag {
     let name = lexer
-        .take_while(|token| match token {
+        .take_while(|(_, token)| match token {
             Token::Identifer("bag") | Token::Identifer("bags") => false,
             Token::Identifer(_) => true,
             _ => false,
         })
-        .map(|token| match token {
+        .map(|(_, token)| match token {
             Token::Identifer(val) => val,
             _ => "",
         })
@@ -135,95 +155,91 @@ fn parse_bag(lexer: &mut Peekable<Lexer>) -> Bag {
     Bag { name, count: 1 }
 }
 
-fn parse_bag_with_count(lexer: &mut Peekable<Lexer>) -> Bag {
+fn parse_bag_with_count(lexer: &mut Peekable<Lexer>, end: usize) -> ParseResult<Bag> {
     let count = match lexer.next() {
-        Some(Token::Integer(val)) => val,
-        Some(token) => panic!(format!("Unexpected token \"{:?}\"", token)),
-        None => panic!("Unexpected end of input"),
+        Some((_, Token::Integer(val))) => val,
+        token => return Err(unexpected(token, end)),
     };
 
-    Bag {
+    Ok(Bag {
         name: parse_bag(lexer).name,
         count,
-    }
+    })
 }
 
-struct TableNode {
-    parents: Vec<Bag>,
-    children: Vec<Bag>,
+/// The bags that directly contain a bag, and the bags it directly contains.
+#[derive(Debug, Default)]
+pub struct TableNode {
+    pub parents: Vec<Bag>,
+    pub children: Vec<Bag>,
 }
 
-impl TableNode {
-    pub fn new() -> Self {
-        Self {
-            parents: vec![],
-            children: vec![],
-        }
-    }
-}
+/// Every bag named by the rules, keyed by colour.
+pub type LookupTable = HashMap<String, TableNode>;
 
-type LookupTable = HashMap<String, TableNode>;
-
-fn parse_rules(lexer: &mut Peekable<Lexer>) -> LookupTable {
+fn parse_rule_list(lexer: &mut Peekable<Lexer>, end: usize) -> ParseResult<LookupTable> {
     let mut lookup_table = LookupTable::new();
 
-    while let Some(token) = lexer.peek() {
+    while let Some((_, token)) = lexer.peek() {
         match token {
             Token::Identifer(_) => {
-                let (container, children) = parse_rule(lexer);
-                let children = children.unwrap_or_else(|| vec![]);
+                let (container, children) = parse_rule(lexer, end)?;
+                let children = children.unwrap_or_default();
 
                 lookup_table
                     .entry(container.clone().name)
-                    .or_insert(TableNode::new())
+                    .or_default()
                     .children
                     .extend(children.clone());
 
                 for child in children {
                     lookup_table
                         .entry(child.name)
-                        .or_insert(TableNode::new())
+                        .or_default()
                         .parents
                         .push(container.clone());
                 }
             }
-            _ => panic!(format!("Unexpected token \"{:?}\"", token)),
+            _ => return Err(unexpected(lexer.next(), end)),
         }
     }
 
-    lookup_table
+    Ok(lookup_table)
 }
 
-fn parse_rule(lexer: &mut Peekable<Lexer>) -> (Bag, Option<Vec<Bag>>) {
+fn parse_rule(lexer: &mut Peekable<Lexer>, end: usize) -> ParseResult<(Bag, Option<Vec<Bag>>)> {
     let container_bag = parse_bag(lexer);
 
     match lexer.next() {
-        Some(Token::Identifer("contain")) => (),
-        Some(token) => panic!(format!("Expected 'contain', found \"{:?}\"", token)),
-        None => panic!("Unexpected end of input"),
+        Some((_, Token::Identifer("contain"))) => (),
+        Some((position, token)) => {
+            return Err((
+                position,
+                format!("Expected 'contain', found \"{:?}\"", token),
+            ))
+        }
+        None => return Err(unexpected(None, end)),
     };
 
-    match lexer.peek() {
-        Some(Token::Identifer("no")) => {
-            lexer.skip(3).next();
-            return (container_bag, None);
-        }
-        _ => (),
+    if let Some((_, Token::Identifer("no"))) = lexer.peek() {
+        lexer.nth(3);
+        return Ok((container_bag, None));
     };
 
-    let mut inner_bags = vec![parse_bag_with_count(lexer)];
-    while let Some(token) = lexer.next() {
+    let mut inner_bags = vec![parse_bag_with_count(lexer, end)?];
+    while let Some((position, token)) = lexer.next() {
         match token {
-            Token::Comma => inner_bags.push(parse_bag_with_count(lexer)),
+            Token::Comma => inner_bags.push(parse_bag_with_count(lexer, end)?),
             Token::Dot => break,
-            _ => panic!(format!("Unexpected token \"{:?}\"", token)),
+            _ => return Err(unexpected(Some((position, token)), end)),
         }
     }
 
-    (container_bag, Some(inner_bags))
+    Ok((container_bag, Some(inner_bags)))
 }
 
-fn get_parents<'a>(bag_name: &str, lookup_table: &'a LookupTable) -> HashSet<&'a str> {
+/// Finds every bag that eventually contains `bag_name`.
+pub fn get_parents<'a>(bag_name: &str, lookup_table: &'a LookupTable) -> HashSet<&'a str> {
     let parents = match lookup_table.get(bag_name) {
         Some(value) => &value.parents,
         None => return HashSet::new(),
@@ -236,7 +252,8 @@ fn get_parents<'a>(bag_name: &str, lookup_table: &'a LookupTable) -> HashSet<&'a
     })
 }
 
-fn get_required_bag_count(bag_name: &str, lookup_table: &LookupTable) -> i32 {
+/// Counts the bags required inside `bag_name`.
+pub fn get_required_bag_count(bag_name: &str, lookup_table: &LookupTable) -> i32 {
     let children = match lookup_table.get(bag_name) {
         Some(value) => &value.children,
         None => return 0,
@@ -247,15 +264,101 @@ fn get_required_bag_count(bag_name: &str, lookup_table: &LookupTable) -> i32 {
     })
 }
 
-pub fn run() -> AppResult<()> {
-    let mut lexer = Lexer::new(INPUT).peekable();
-    let hash = parse_rules(&mut lexer);
+/// Parses a list of bag rules like
+/// `light red bags contain 1 bright white bag, 2 muted yellow bags.`
+pub fn parse_rules(input: &str) -> AppResult<LookupTable> {
+    let mut lexer = Lexer::new(input).peekable();
+
+    parse_rule_list(&mut lexer, input.len())
+        .map_err(|(offset, message)| ParseError::at_offset(input, offset, &message).into())
+}
+
+/// Day 7: Handy Haversacks.
+pub struct HandyHaversacks;
+
+impl Solution for HandyHaversacks {
+    type Input = LookupTable;
+    type Answer = usize;
+
+    fn parse(&self, input: &str) -> AppResult<Self::Input> {
+        parse_rules(input)
+    }
+
+    fn part1(&self, lookup_table: &Self::Input) -> AppResult<Self::Answer> {
+        Ok(get_parents("shiny gold", lookup_table).len())
+    }
+
+    fn part2(&self, lookup_table: &Self::Input) -> AppResult<Self::Answer> {
+        Ok(get_required_bag_count("shiny gold", lookup_table) as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::AppError;
+
+    const EXAMPLE: &str = "\
+light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.";
+
+    const NESTED_EXAMPLE: &str = "\
+shiny gold bags contain 2 dark red bags.
+dark red bags contain 2 dark orange bags.
+dark orange bags contain 2 dark yellow bags.
+dark yellow bags contain 2 dark green bags.
+dark green bags contain 2 dark blue bags.
+dark blue bags contain 2 dark violet bags.
+dark violet bags contain no other bags.";
+
+    #[test]
+    fn part1_example() {
+        let rules = HandyHaversacks.parse(EXAMPLE).unwrap();
+
+        assert_eq!(HandyHaversacks.part1(&rules).unwrap(), 4);
+    }
 
-    println!("Part 1: \"{}\"", get_parents("shiny gold", &hash).len());
-    println!(
-        "Part 2: \"{}\"",
-        get_required_bag_count("shiny gold", &hash)
-    );
+    #[test]
+    fn part2_examples() {
+        let rules = HandyHaversacks.parse(EXAMPLE).unwrap();
+        assert_eq!(HandyHaversacks.part2(&rules).unwrap(), 32);
+
+        let rules = HandyHaversacks.parse(NESTED_EXAMPLE).unwrap();
+        assert_eq!(HandyHaversacks.part2(&rules).unwrap(), 126);
+    }
 
-    Ok(())
+    #[test]
+    fn lexer_tokens() {
+        let tokens = Lexer::new("2 shiny gold bags, no.")
+            .map(|(position, token)| (position, format!("{:?}", token)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            tokens,
+            vec![
+                (0, "Integer(2)".to_string()),
+                (2, "Identifer(\"shiny\")".to_string()),
+                (8, "Identifer(\"gold\")".to_string()),
+                (13, "Identifer(\"bags\")".to_string()),
+                (17, "Comma".to_string()),
+                (19, "Identifer(\"no\")".to_string()),
+                (21, "Dot".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_contain() {
+        match HandyHaversacks.parse("light red bags hold 1 bright white bag.") {
+            Err(AppError::Parse(err)) => assert_eq!((err.line, err.column), (1, 16)),
+            result => panic!("Expected a parse error, found {:?}", result.map(|_| ())),
+        }
+    }
 }
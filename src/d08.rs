@@ -1,33 +1,36 @@
-use std::iter::repeat_with;
+use std::convert::TryFrom;
 
-use crate::errors::AppResult;
-
-const INPUT: &'static str = include_str!("../data/boot-code.txt");
+use crate::errors::{AppError, AppResult, ParseError};
+use crate::solution::Solution;
 
+/// A boot code instruction and its argument.
 #[derive(Debug, Clone)]
-enum IntCode {
+pub enum IntCode {
     Accum(i32),
     Jump(i32),
     Noop(i32),
 }
 
+/// Why a program stopped, `Cycle` meaning an instruction was about to run a
+/// second time.
 #[derive(Debug, Eq, PartialEq)]
-enum RunState {
+pub enum RunState {
     Running,
     Cycle,
     Terminated,
 }
 
+/// The state of the console, including every instruction run so far.
 #[derive(Debug)]
-struct State {
-    position: i32,
-    accumulator: i32,
-    run_state: RunState,
-    int_codes: Vec<IntCode>,
+pub struct State {
+    pub position: i32,
+    pub accumulator: i32,
+    pub run_state: RunState,
+    pub int_codes: Vec<IntCode>,
 }
 
-impl State {
-    pub fn new() -> Self {
+impl Default for State {
+    fn default() -> Self {
         Self {
             position: 0,
             accumulator: 0,
@@ -37,108 +40,219 @@ impl State {
     }
 }
 
-fn run_program(boot_code: &Vec<(i32, IntCode)>) -> State {
-    let mut boot_code = boot_code.clone();
-    let mut state = State::new();
+// Adds `value` to `register`, failing rather than wrapping around.
+fn add(register: i32, value: i32, name: &str) -> AppResult<i32> {
+    register.checked_add(value).ok_or_else(|| {
+        AppError::unsolvable(&format!(
+            "Adding {} to the {} {} overflows",
+            value, name, register
+        ))
+    })
+}
+
+/// Runs `boot_code` until it either terminates by running past its last
+/// instruction, or loops. Each instruction is paired with the number of times
+/// it has already run, which is `0` for a freshly parsed program.
+///
+/// Fails when the program jumps before its first instruction, or when the
+/// position or the accumulator overflows.
+pub fn run_program(boot_code: &[(i32, IntCode)]) -> AppResult<State> {
+    let mut boot_code = boot_code.to_vec();
+    let mut state = State::default();
 
     while state.run_state == RunState::Running {
-        if state.position as usize >= boot_code.len() {
-            state.run_state = RunState::Terminated;
-            return state;
-        }
+        let position = usize::try_from(state.position).map_err(|_| {
+            AppError::unsolvable(&format!(
+                "Jumped to instruction {}, before the first",
+                state.position
+            ))
+        })?;
 
-        let (call_count, int_code) = boot_code
-            .get_mut(state.position as usize)
-            .expect("Position out of bounds");
+        let (call_count, int_code) = match boot_code.get_mut(position) {
+            Some(instruction) => instruction,
+            None => {
+                state.run_state = RunState::Terminated;
+                return Ok(state);
+            }
+        };
 
         if *call_count >= 1 {
             state.run_state = RunState::Cycle;
-            return state;
+            return Ok(state);
         }
 
         match int_code {
             IntCode::Accum(value) => {
-                state.accumulator += *value;
-                state.position += 1;
+                state.accumulator = add(state.accumulator, *value, "accumulator")?;
+                state.position = add(state.position, 1, "position")?;
                 state.int_codes.push(IntCode::Accum(*value));
             }
             IntCode::Jump(value) => {
-                state.position += *value;
+                state.position = add(state.position, *value, "position")?;
                 state.int_codes.push(IntCode::Jump(*value));
             }
             IntCode::Noop(value) => {
                 state.int_codes.push(IntCode::Noop(*value));
-                state.position += 1;
+                state.position = add(state.position, 1, "position")?;
             }
         }
 
         *call_count += 1;
     }
 
-    state
+    Ok(state)
 }
 
-pub fn run() -> AppResult<()> {
-    let boot_code = INPUT
-        .split("\n")
-        .map(|line| {
-            let instruction = line.split(" ").collect::<Vec<_>>();
-            let int_code = match *instruction.get(0).expect("Missing int code") {
-                "nop" => IntCode::Noop(
-                    instruction
-                        .get(1)
-                        .expect("Missing noop argument")
-                        .parse::<i32>()
-                        .expect("Failed to parse noop argument"),
-                ),
-                "acc" => IntCode::Accum(
-                    instruction
-                        .get(1)
-                        .expect("Missing acc argument")
-                        .parse::<i32>()
-                        .expect("Failed to parse acc argument"),
-                ),
-                "jmp" => IntCode::Jump(
-                    instruction
-                        .get(1)
-                        .expect("Missing jmp argument")
-                        .parse::<i32>()
-                        .expect("Failed to parse jmp argument"),
-                ),
-                value => panic!(format!("Unexpected int code \"{}\"", value)),
-            };
+/// Parses an instruction like `acc +1`.
+pub fn parse_int_code(line_number: usize, line: &str) -> AppResult<IntCode> {
+    let mut instruction = line.splitn(2, ' ');
+    let name = instruction.next().unwrap_or(line);
 
-            (0, int_code)
-        })
-        .collect::<Vec<_>>();
+    let int_code: fn(i32) -> IntCode = match name {
+        "nop" => IntCode::Noop,
+        "acc" => IntCode::Accum,
+        "jmp" => IntCode::Jump,
+        value => {
+            return Err(ParseError::at_token(
+                line_number,
+                line,
+                name,
+                &format!("Unexpected int code \"{}\"", value),
+            )
+            .into())
+        }
+    };
+
+    let argument = instruction.next().ok_or_else(|| {
+        ParseError::at_token(
+            line_number,
+            line,
+            &line[line.len()..],
+            &format!("Missing {} argument", name),
+        )
+    })?;
 
-    let state = run_program(&boot_code);
+    let value = argument.parse::<i32>().map_err(|err| {
+        ParseError::at_token(
+            line_number,
+            line,
+            argument,
+            &format!("Failed to parse {} argument", name),
+        )
+        .with_source(err)
+    })?;
 
-    println!("Part 1 {:?}", state.accumulator);
+    Ok(int_code(value))
+}
 
-    let mut count = 0;
-    let result2 = repeat_with(|| boot_code.clone())
-        .find_map(|mut int_codes| {
+/// Swaps a single `jmp` for a `nop`, or a `nop` for a `jmp`, until the
+/// program terminates, and returns the accumulator it terminated with.
+pub fn repair_program(boot_code: &[(i32, IntCode)]) -> AppResult<i32> {
+    (0..boot_code.len())
+        .find_map(|count| {
+            let mut int_codes = boot_code.to_vec();
             int_codes[count] = match &int_codes[count] {
                 (c, IntCode::Noop(value)) => (*c, IntCode::Jump(*value)),
                 (c, IntCode::Jump(value)) => (*c, IntCode::Noop(*value)),
-                (c, IntCode::Accum(value)) => (*c, IntCode::Accum(*value)),
+                (_, IntCode::Accum(_)) => return None,
             };
 
-            count += 1;
-
+            // a swap making the program fail isn't the repair either
             match run_program(&int_codes) {
-                State {
+                Ok(State {
                     run_state: RunState::Terminated,
                     accumulator,
                     ..
-                } => Some(accumulator),
+                }) => Some(accumulator),
                 _ => None,
             }
         })
-        .expect("Failed to find valid program");
+        .ok_or_else(|| AppError::unsolvable("Failed to find valid program"))
+}
+
+/// Day 8: Handheld Halting.
+pub struct HandheldHalting;
+
+impl Solution for HandheldHalting {
+    type Input = Vec<(i32, IntCode)>;
+    type Answer = i32;
+
+    fn parse(&self, input: &str) -> AppResult<Self::Input> {
+        input
+            .split('\n')
+            .enumerate()
+            .map(|(index, line)| Ok((0, parse_int_code(index + 1, line)?)))
+            .collect()
+    }
+
+    fn part1(&self, boot_code: &Self::Input) -> AppResult<Self::Answer> {
+        Ok(run_program(boot_code)?.accumulator)
+    }
 
-    println!("Part 2 {:?}", result2);
+    fn part2(&self, boot_code: &Self::Input) -> AppResult<Self::Answer> {
+        repair_program(boot_code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6";
+
+    #[test]
+    fn part1_example() {
+        let boot_code = HandheldHalting.parse(EXAMPLE).unwrap();
+
+        assert_eq!(HandheldHalting.part1(&boot_code).unwrap(), 5);
+    }
+
+    #[test]
+    fn part2_example() {
+        let boot_code = HandheldHalting.parse(EXAMPLE).unwrap();
+
+        assert_eq!(HandheldHalting.part2(&boot_code).unwrap(), 8);
+    }
+
+    #[test]
+    fn jumps_out_of_the_program() {
+        let boot_code = HandheldHalting.parse("nop +0\njmp -2").unwrap();
+        assert!(matches!(
+            HandheldHalting.part1(&boot_code),
+            Err(AppError::Unsolvable(_))
+        ));
+
+        let boot_code = HandheldHalting.parse("jmp +2147483647").unwrap();
+        assert_eq!(
+            run_program(&boot_code).unwrap().run_state,
+            RunState::Terminated
+        );
+
+        let boot_code = HandheldHalting.parse("nop +0\njmp +2147483647").unwrap();
+        assert!(matches!(
+            HandheldHalting.part1(&boot_code),
+            Err(AppError::Unsolvable(_))
+        ));
+    }
 
-    Ok(())
+    #[test]
+    fn accumulator_overflow() {
+        let boot_code = HandheldHalting.parse("acc +2147483647\nacc +1").unwrap();
+
+        assert!(HandheldHalting.part1(&boot_code).is_err());
+    }
+
+    #[test]
+    fn unknown_instruction() {
+        assert!(HandheldHalting.parse("nop +0\nmul +2").is_err());
+    }
 }
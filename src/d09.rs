@@ -1,8 +1,10 @@
 use std::{cmp, collections::HashSet};
 
-use crate::errors::AppResult;
+use crate::errors::{AppError, AppResult, ParseError};
+use crate::solution::Solution;
 
-const INPUT: &'static str = include_str!("../data/xmas-data.txt");
+/// The 25 number preamble plus the number being checked.
+pub const WINDOW_SIZE: usize = 26;
 
 fn has_match<'a>(value: &u64, preamble: &'a [u64]) -> Option<&'a u64> {
     let preamble_set = preamble.iter().collect::<HashSet<_>>();
@@ -14,49 +16,54 @@ fn has_match<'a>(value: &u64, preamble: &'a [u64]) -> Option<&'a u64> {
             let max = cmp::max(value, x);
             preamble_set.get(&(max - min))
         })
-        .map(|&x| x)
+        .copied()
 }
 
-pub fn run() -> AppResult<()> {
-    let encrypted_data = INPUT
-        .split("\n")
-        .map(|x| x.parse::<u64>().expect("Failed to parse line as u64"))
-        .collect::<Vec<_>>();
+/// Finds the index of the first number that isn't the sum of two of the
+/// `window_size - 1` numbers before it.
+pub fn find_corrupt_location(encrypted_data: &[u64], window_size: usize) -> Option<usize> {
+    if window_size < 2 {
+        return None;
+    }
 
-    let window_size = 26;
     let mut location = window_size - 1;
     let mut sliding_window = encrypted_data.windows(window_size);
 
-    let corrupt_location = sliding_window
-        .find_map(|window| {
-            match has_match(&window[window_size - 1], &window[..(window_size - 1)]) {
-                Some(_) => {
-                    location += 1;
-                    None
-                }
-                None => Some(location),
+    sliding_window.find_map(|window| {
+        match has_match(&window[window_size - 1], &window[..(window_size - 1)]) {
+            Some(_) => {
+                location += 1;
+                None
             }
-        })
-        .expect("Could not find corrupt location");
-
-    let corrupt_value = encrypted_data[corrupt_location];
-
-    println!("Part 1: \"{:?}\"", corrupt_value);
+            None => Some(location),
+        }
+    })
+}
 
+/// Finds a contiguous run of at least two numbers before `corrupt_location`
+/// that sums to the corrupt number, and returns the sum of its smallest and
+/// largest numbers.
+pub fn find_encryption_weakness(encrypted_data: &[u64], corrupt_location: usize) -> Option<u64> {
+    let corrupt_value = *encrypted_data.get(corrupt_location)?;
     let mut cursor = corrupt_location;
     let mut size = 2;
 
     while cursor > 0 {
+        if size > cursor {
+            cursor -= 1;
+            size = 2;
+            continue;
+        }
+
         let slice = &encrypted_data[(cursor - size)..cursor];
         size += 1;
 
-        let sum = slice.iter().fold(0, |acc, x| acc + x);
+        let sum = slice.iter().sum::<u64>();
 
         if sum == corrupt_value {
-            let min = slice.iter().min().expect("No min value in range");
-            let max = slice.iter().max().expect("No max value in range");
-            println!("Part 2: \"{:?}\"", min + max);
-            break;
+            let min = slice.iter().min()?;
+            let max = slice.iter().max()?;
+            return Some(min + max);
         }
 
         if sum > corrupt_value {
@@ -65,5 +72,85 @@ pub fn run() -> AppResult<()> {
         }
     }
 
-    Ok(())
+    None
+}
+
+/// Day 9: Encoding Error.
+pub struct EncodingError;
+
+impl Solution for EncodingError {
+    type Input = Vec<u64>;
+    type Answer = u64;
+
+    fn parse(&self, input: &str) -> AppResult<Self::Input> {
+        input
+            .split('\n')
+            .enumerate()
+            .map(|(index, line)| {
+                line.parse::<u64>().map_err(|err| {
+                    ParseError::new(index + 1, 1, "Failed to parse line as u64")
+                        .with_source(err)
+                        .into()
+                })
+            })
+            .collect()
+    }
+
+    fn part1(&self, encrypted_data: &Self::Input) -> AppResult<Self::Answer> {
+        let corrupt_location = find_corrupt_location(encrypted_data, WINDOW_SIZE)
+            .ok_or_else(|| AppError::unsolvable("Could not find corrupt location"))?;
+
+        Ok(encrypted_data[corrupt_location])
+    }
+
+    fn part2(&self, encrypted_data: &Self::Input) -> AppResult<Self::Answer> {
+        let corrupt_location = find_corrupt_location(encrypted_data, WINDOW_SIZE)
+            .ok_or_else(|| AppError::unsolvable("Could not find corrupt location"))?;
+
+        find_encryption_weakness(encrypted_data, corrupt_location)
+            .ok_or_else(|| AppError::unsolvable("Could not find encryption weakness"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the example uses a preamble of 5 instead of 25
+    const EXAMPLE: &str = "\
+35
+20
+15
+25
+47
+40
+62
+55
+65
+95
+102
+117
+150
+182
+127
+219
+299
+277
+309
+576";
+
+    #[test]
+    fn corrupt_location_example() {
+        let data = EncodingError.parse(EXAMPLE).unwrap();
+
+        assert_eq!(find_corrupt_location(&data, 6), Some(14));
+        assert_eq!(data[14], 127);
+    }
+
+    #[test]
+    fn encryption_weakness_example() {
+        let data = EncodingError.parse(EXAMPLE).unwrap();
+
+        assert_eq!(find_encryption_weakness(&data, 14), Some(62));
+    }
 }
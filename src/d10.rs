@@ -1,61 +1,185 @@
 use cached::proc_macro::cached;
 
-use crate::errors::AppResult;
+use crate::errors::{AppError, AppResult, ParseError};
+use crate::solution::Solution;
 
-const INPUT: &'static str = include_str!("../data/jolts-adapters.txt");
-
-fn calculate_jolt_difference(adapters: &[u16]) -> u16 {
+/// Multiplies the number of 1-jolt differences by the number of 3-jolt
+/// differences between sorted `adapters`, which start with the outlet's 0.
+pub fn calculate_jolt_difference(adapters: &[u32]) -> AppResult<u64> {
     let (_, one_jolt, three_jolt) =
         // skip 0
-        adapters[1..]
+        adapters
             .iter()
-            .fold((0, 0, 0), |acc, &current| match current - acc.0 {
-                1 => (current, acc.1 + 1, acc.2),
-                3 => (current, acc.1, acc.2 + 1),
-                val => panic!(format!("Unexpected difference {}", val)),
-            });
+            .skip(1)
+            .try_fold((0, 0u64, 0u64), |acc, &current| match current - acc.0 {
+                1 => Ok((current, acc.1 + 1, acc.2)),
+                3 => Ok((current, acc.1, acc.2 + 1)),
+                val => Err(AppError::unsolvable(&format!("Unexpected difference {}", val))),
+            })?;
 
-    one_jolt * three_jolt
+    Ok(one_jolt * three_jolt)
 }
 
+/// Counts the arrangements of sorted `adapters` connecting the first adapter
+/// to one rated `outlet_jolts`, or `None` when there are more than fit in a
+/// u64.
 #[cached]
-fn calculate_permutation_count(adapters: Vec<u64>, outlet_jolts: u64) -> u64 {
+pub fn calculate_permutation_count(adapters: Vec<u64>, outlet_jolts: u64) -> Option<u64> {
     match adapters.len() {
+        0 => Some(0),
         1 => match adapters[0] == outlet_jolts {
-            true => return 1,
-            false => return 0,
+            true => Some(1),
+            false => Some(0),
         },
         _ => adapters
             .iter()
             .enumerate()
             .skip(1)
             .take_while(|(_, &x)| x - adapters[0] <= 3)
-            .fold(0, |acc, (i, _)| {
-                acc + calculate_permutation_count(adapters[i..].to_vec(), outlet_jolts)
+            .try_fold(0u64, |acc, (i, _)| {
+                acc.checked_add(calculate_permutation_count(
+                    adapters[i..].to_vec(),
+                    outlet_jolts,
+                )?)
             }),
     }
 }
 
-pub fn run() -> AppResult<()> {
-    let mut adapters = INPUT
-        .split("\n")
-        .map(|x| x.parse::<u16>().unwrap())
-        .collect::<Vec<_>>();
+/// Day 10: Adapter Array.
+pub struct AdapterArray;
+
+impl Solution for AdapterArray {
+    type Input = Vec<u32>;
+    type Answer = u64;
+
+    fn parse(&self, input: &str) -> AppResult<Self::Input> {
+        let mut adapters = input
+            .split('\n')
+            .enumerate()
+            .map(|(index, line)| {
+                line.parse::<u32>().map_err(|err| {
+                    ParseError::new(index + 1, 1, &format!("Invalid adapter \"{}\"", line))
+                        .with_source(err)
+                        .into()
+                })
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        adapters.push(0);
+        adapters.sort_unstable();
+
+        let outlet_jolts = adapters
+            .last()
+            .ok_or_else(|| AppError::invalid_input("No adapters"))?
+            .checked_add(3)
+            .ok_or_else(|| AppError::invalid_input("Adapter rating is too high"))?;
+        adapters.push(outlet_jolts);
 
-    adapters.push(0);
-    adapters.sort();
+        Ok(adapters)
+    }
+
+    fn part1(&self, adapters: &Self::Input) -> AppResult<Self::Answer> {
+        calculate_jolt_difference(adapters)
+    }
+
+    fn part2(&self, adapters: &Self::Input) -> AppResult<Self::Answer> {
+        let outlet_jolts = *adapters
+            .last()
+            .ok_or_else(|| AppError::invalid_input("No adapters"))?;
+
+        calculate_permutation_count(
+            adapters.iter().map(|&x| x as u64).collect::<Vec<_>>(),
+            outlet_jolts as u64,
+        )
+        .ok_or_else(|| AppError::unsolvable("Adapter arrangements overflow a u64"))
+    }
+}
 
-    let outlet_jolts = adapters.last().unwrap() + 3;
-    adapters.push(outlet_jolts);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    println!("Part 1: \"{}\"", calculate_jolt_difference(&adapters));
+    const SMALL_EXAMPLE: &str = "16\n10\n15\n5\n1\n11\n7\n19\n6\n12\n4";
 
-    let part2 = calculate_permutation_count(
-        adapters.iter().map(|&x| x as u64).collect::<Vec<_>>(),
-        outlet_jolts as u64,
-    );
+    const LARGE_EXAMPLE: &str = "\
+28
+33
+18
+42
+31
+14
+46
+20
+48
+47
+24
+23
+49
+45
+19
+38
+39
+11
+1
+32
+25
+35
+8
+17
+7
+9
+4
+2
+34
+10
+3";
 
-    println!("Part 2: \"{}\"", part2);
+    #[test]
+    fn part1_examples() {
+        let adapters = AdapterArray.parse(SMALL_EXAMPLE).unwrap();
+        assert_eq!(AdapterArray.part1(&adapters).unwrap(), 7 * 5);
 
-    Ok(())
+        let adapters = AdapterArray.parse(LARGE_EXAMPLE).unwrap();
+        assert_eq!(AdapterArray.part1(&adapters).unwrap(), 22 * 10);
+    }
+
+    #[test]
+    fn part2_examples() {
+        let adapters = AdapterArray.parse(SMALL_EXAMPLE).unwrap();
+        assert_eq!(AdapterArray.part2(&adapters).unwrap(), 8);
+
+        let adapters = AdapterArray.parse(LARGE_EXAMPLE).unwrap();
+        assert_eq!(AdapterArray.part2(&adapters).unwrap(), 19208);
+    }
+
+    #[test]
+    fn unexpected_difference() {
+        let adapters = AdapterArray.parse("1\n6").unwrap();
+
+        assert!(AdapterArray.part1(&adapters).is_err());
+    }
+
+    #[test]
+    fn large_ratings() {
+        let adapters = AdapterArray.parse("65535").unwrap();
+        assert!(AdapterArray.part1(&adapters).is_err());
+
+        assert!(AdapterArray.parse("4294967295").is_err());
+    }
+
+    #[test]
+    fn long_chains() {
+        let chain = (1..=300)
+            .chain((1..=300).map(|step| 300 + 3 * step))
+            .map(|jolts| jolts.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let adapters = AdapterArray.parse(&chain).unwrap();
+
+        assert_eq!(AdapterArray.part1(&adapters).unwrap(), 300 * 301);
+        assert!(matches!(
+            AdapterArray.part2(&adapters),
+            Err(AppError::Unsolvable(_))
+        ));
+    }
 }
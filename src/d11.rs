@@ -1,12 +1,11 @@
 use std::{
     collections::hash_map::DefaultHasher,
-    convert::TryInto,
     hash::{Hash, Hasher},
 };
 
 use crate::errors::AppResult;
-
-const INPUT: &'static str = include_str!("../data/ferry-seats.txt");
+use crate::grid::TilingGrid;
+use crate::solution::Solution;
 
 // 1 2 3
 // 4 X 6
@@ -22,10 +21,13 @@ const ADJACENT_VELOCITY: [(isize, isize); 8] = [
     (1, 1),
 ];
 
+/// How people pick seats. With `allow_seat_distance` they look past the
+/// floor to the first seat in each direction instead of only the adjacent
+/// ones, and they leave once `tolerated_occupied_seats` of them are taken.
 #[derive(Debug)]
-struct Rules {
-    allow_seat_distance: bool,
-    tolerated_occupied_seats: usize,
+pub struct Rules {
+    pub allow_seat_distance: bool,
+    pub tolerated_occupied_seats: usize,
 }
 
 fn compute_hash<T: Hash>(value: &T) -> u64 {
@@ -34,123 +36,136 @@ fn compute_hash<T: Hash>(value: &T) -> u64 {
     hasher.finish()
 }
 
-fn get_cell_at<'a>(
-    cells: &'a [Vec<char>],
-    position: &(usize, usize),
-    velocity: &(isize, isize),
-) -> Option<((usize, usize), &'a char)> {
-    let row: usize = (position.0 as isize + velocity.0).try_into().ok()?;
-    let col: usize = (position.1 as isize + velocity.1).try_into().ok()?;
-    let cell = cells.get(row)?.get(col)?;
-    // how much data can I cram into a single return value?
-    let result = ((row, col), cell);
-
-    Some(result)
-}
-
-fn get_next_seat<'a>(
-    cells: &'a [Vec<char>],
+/// Counts the occupied seats seen from the `(x, y)` `position`.
+pub fn count_occupied(
+    cells: &TilingGrid<char>,
     position: &(usize, usize),
-    velocity: &(isize, isize),
-) -> Option<&'a char> {
-    match get_cell_at(cells, position, velocity)? {
-        (next, '.') => get_next_seat(cells, &next, velocity),
-        (_, cell) => Some(cell),
-    }
-}
-
-fn count_occupied(cells: &[Vec<char>], position: &(usize, usize), with_distance: bool) -> usize {
+    with_distance: bool,
+) -> usize {
     ADJACENT_VELOCITY
         .iter()
-        .map(|velocity| match with_distance {
-            true => get_next_seat(cells, position, velocity),
-            false => get_cell_at(cells, position, velocity).map(|(_, cell)| cell),
+        .filter_map(|&velocity| {
+            let mut seen = cells.ray(*position, velocity).map(|(_, cell)| cell);
+
+            match with_distance {
+                true => seen.find(|&&cell| cell != '.'),
+                false => seen.next(),
+            }
         })
-        .filter_map(|x| x)
         .filter(|&&x| x == '#')
         .count()
 }
 
-fn calculate_updates(cells: &[Vec<char>], rules: &Rules) -> Vec<(usize, usize, char)> {
+fn calculate_updates(cells: &TilingGrid<char>, rules: &Rules) -> Vec<((usize, usize), char)> {
     let Rules {
         allow_seat_distance,
         tolerated_occupied_seats,
     } = rules;
 
     cells
-        .iter()
-        .enumerate()
-        .fold(vec![], |acc, (row_index, row)| {
-            row.iter()
-                .enumerate()
-                .fold(acc, |mut acc, (cell_index, value)| match value {
-                    'L' if count_occupied(
-                        cells,
-                        &(row_index, cell_index),
-                        *allow_seat_distance,
-                    ) == 0 =>
-                    {
-                        acc.push((row_index, cell_index, '#'));
-                        acc
-                    }
-                    '#' if count_occupied(
-                        cells,
-                        &(row_index, cell_index),
-                        *allow_seat_distance,
-                    ) >= *tolerated_occupied_seats =>
-                    {
-                        acc.push((row_index, cell_index, 'L'));
-                        acc
-                    }
-                    _ => acc,
-                })
+        .cells()
+        .filter_map(|(position, value)| match value {
+            'L' if count_occupied(cells, &position, *allow_seat_distance) == 0 => {
+                Some((position, '#'))
+            }
+            '#' if count_occupied(cells, &position, *allow_seat_distance)
+                >= *tolerated_occupied_seats =>
+            {
+                Some((position, 'L'))
+            }
+            _ => None,
         })
+        .collect()
 }
 
-fn simulate_people_sitting_habits(cells: &mut [Vec<char>], rules: &Rules) -> usize {
+/// Applies `rules` to the seat layout until nobody moves, and returns the
+/// number of occupied seats.
+pub fn simulate_people_sitting_habits(cells: &mut TilingGrid<char>, rules: &Rules) -> usize {
     let mut changed = true;
     while changed {
         let hash = compute_hash(&cells);
 
-        for (row_index, cell_index, value) in calculate_updates(&cells, rules) {
-            cells[row_index][cell_index] = value;
+        for (position, value) in calculate_updates(cells, rules) {
+            if let Some(cell) = cells.get_mut(position) {
+                *cell = value;
+            }
         }
 
         changed = hash != compute_hash(&cells);
     }
 
-    let occupied_seats = cells.iter().fold(0, |acc, row| {
-        acc + row.iter().filter(|cell| **cell == '#').count()
-    });
+    cells.cells().filter(|(_, &cell)| cell == '#').count()
+}
+
+/// Day 11: Seating System.
+pub struct SeatingSystem;
+
+impl Solution for SeatingSystem {
+    type Input = TilingGrid<char>;
+    type Answer = usize;
+
+    fn parse(&self, input: &str) -> AppResult<Self::Input> {
+        TilingGrid::parse(input)
+    }
+
+    fn part1(&self, cells: &Self::Input) -> AppResult<Self::Answer> {
+        Ok(simulate_people_sitting_habits(
+            &mut cells.clone(),
+            &Rules {
+                allow_seat_distance: false,
+                tolerated_occupied_seats: 4,
+            },
+        ))
+    }
 
-    occupied_seats
+    fn part2(&self, cells: &Self::Input) -> AppResult<Self::Answer> {
+        Ok(simulate_people_sitting_habits(
+            &mut cells.clone(),
+            &Rules {
+                allow_seat_distance: true,
+                tolerated_occupied_seats: 5,
+            },
+        ))
+    }
 }
 
-pub fn run() -> AppResult<()> {
-    let cells = INPUT
-        .split("\n")
-        .map(|line| line.chars().collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-
-    let part1 = simulate_people_sitting_habits(
-        &mut cells.clone(),
-        &Rules {
-            allow_seat_distance: false,
-            tolerated_occupied_seats: 4,
-        },
-    );
-
-    println!("Part 1: \"{}\"", part1);
-
-    let part2 = simulate_people_sitting_habits(
-        &mut cells.clone(),
-        &Rules {
-            allow_seat_distance: true,
-            tolerated_occupied_seats: 5,
-        },
-    );
-
-    println!("Part 2: \"{}\"", part2);
-
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL";
+
+    #[test]
+    fn part1_example() {
+        let cells = SeatingSystem.parse(EXAMPLE).unwrap();
+
+        assert_eq!(SeatingSystem.part1(&cells).unwrap(), 37);
+    }
+
+    #[test]
+    fn part2_example() {
+        let cells = SeatingSystem.parse(EXAMPLE).unwrap();
+
+        assert_eq!(SeatingSystem.part2(&cells).unwrap(), 26);
+    }
+
+    #[test]
+    fn sees_seats_across_the_floor() {
+        let cells = SeatingSystem
+            .parse(".............\n.L.L.#.#.#.#.\n.............")
+            .unwrap();
+
+        assert_eq!(count_occupied(&cells, &(1, 1), true), 0);
+        assert_eq!(count_occupied(&cells, &(3, 1), true), 1);
+    }
 }
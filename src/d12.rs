@@ -1,11 +1,11 @@
-use std::convert::{TryFrom, TryInto};
+use std::convert::TryFrom;
 
-use crate::errors::{AppError, AppResult};
-
-const INPUT: &'static str = include_str!("../data/navigation-instructions.txt");
+use crate::errors::{AppError, AppResult, ParseError};
+use crate::solution::Solution;
 
+/// A navigation instruction like `F10`. Turns are in degrees.
 #[derive(Debug)]
-enum NavInstruction {
+pub enum NavInstruction {
     MoveNorth(i32),
     MoveEast(i32),
     MoveSouth(i32),
@@ -43,7 +43,7 @@ impl TryFrom<i32> for Direction {
             90 => Ok(Direction::East),
             180 => Ok(Direction::South),
             270 => Ok(Direction::West),
-            value => Err(AppError::new(&format!(
+            value => Err(AppError::invalid_input(&format!(
                 "Failed to convert \"{}\" to a Direction",
                 value
             ))),
@@ -51,6 +51,34 @@ impl TryFrom<i32> for Direction {
     }
 }
 
+// How many quarter turns clockwise, from 0 to 3, a turn of `degrees` to the
+// right is. Turns of a full circle or more are allowed.
+fn quarter_turns(degrees: i32) -> AppResult<i32> {
+    match degrees % 90 {
+        0 => Ok((degrees / 90).rem_euclid(4)),
+        _ => Err(AppError::invalid_input(&format!(
+            "Turns must be a multiple of 90 degrees, found {}",
+            degrees
+        ))),
+    }
+}
+
+// Moves `position` by `distance`, `None` meaning the distance itself
+// overflowed.
+fn moved(position: i32, distance: Option<i32>) -> AppResult<i32> {
+    distance
+        .and_then(|distance| position.checked_add(distance))
+        .ok_or_else(|| AppError::unsolvable("Ship sailed too far to track"))
+}
+
+fn manhattan_distance((north, east): (i32, i32)) -> AppResult<i32> {
+    north
+        .checked_abs()
+        .zip(east.checked_abs())
+        .and_then(|(north, east)| north.checked_add(east))
+        .ok_or_else(|| AppError::unsolvable("Ship sailed too far to track"))
+}
+
 #[derive(Debug)]
 struct DirectionalShip {
     direction: Direction,
@@ -65,27 +93,39 @@ impl DirectionalShip {
         }
     }
 
-    pub fn process_instruction(&mut self, instruction: &NavInstruction) {
+    fn turn(&mut self, quarter_turns: i32) -> AppResult<()> {
+        self.direction =
+            Direction::try_from((i32::from(&self.direction) + 90 * quarter_turns) % 360)?;
+        Ok(())
+    }
+
+    pub fn process_instruction(&mut self, instruction: &NavInstruction) -> AppResult<()> {
         match *instruction {
-            NavInstruction::MoveNorth(value) => self.position.0 += value,
-            NavInstruction::MoveSouth(value) => self.position.0 -= value,
-            NavInstruction::MoveEast(value) => self.position.1 += value,
-            NavInstruction::MoveWest(value) => self.position.1 -= value,
-            NavInstruction::MoveForward(value) => self.process_instruction(&match self.direction {
-                Direction::North => NavInstruction::MoveNorth(value),
-                Direction::South => NavInstruction::MoveSouth(value),
-                Direction::East => NavInstruction::MoveEast(value),
-                Direction::West => NavInstruction::MoveWest(value),
-            }),
-            NavInstruction::TurnRight(value) => {
-                let direction = (i32::from(&self.direction) + value) % 360;
-                self.direction = direction.try_into().unwrap();
+            NavInstruction::MoveNorth(value) => {
+                self.position.0 = moved(self.position.0, Some(value))?
+            }
+            NavInstruction::MoveSouth(value) => {
+                self.position.0 = moved(self.position.0, value.checked_neg())?
+            }
+            NavInstruction::MoveEast(value) => {
+                self.position.1 = moved(self.position.1, Some(value))?
             }
-            NavInstruction::TurnLeft(value) => {
-                let direction = ((360 + i32::from(&self.direction)) - value) % 360;
-                self.direction = direction.try_into().unwrap();
+            NavInstruction::MoveWest(value) => {
+                self.position.1 = moved(self.position.1, value.checked_neg())?
             }
+            NavInstruction::MoveForward(value) => {
+                return self.process_instruction(&match self.direction {
+                    Direction::North => NavInstruction::MoveNorth(value),
+                    Direction::South => NavInstruction::MoveSouth(value),
+                    Direction::East => NavInstruction::MoveEast(value),
+                    Direction::West => NavInstruction::MoveWest(value),
+                })
+            }
+            NavInstruction::TurnRight(value) => self.turn(quarter_turns(value)?)?,
+            NavInstruction::TurnLeft(value) => self.turn((4 - quarter_turns(value)?) % 4)?,
         }
+
+        Ok(())
     }
 }
 
@@ -103,90 +143,179 @@ impl WaypointShip {
         }
     }
 
-    pub fn process_instruction(&mut self, instruction: &NavInstruction) {
+    fn turn(&mut self, quarter_turns: i32) {
+        for _ in 0..quarter_turns {
+            self.waypoint = (-self.waypoint.1, self.waypoint.0);
+        }
+    }
+
+    pub fn process_instruction(&mut self, instruction: &NavInstruction) -> AppResult<()> {
         match *instruction {
-            NavInstruction::MoveNorth(value) => self.waypoint.0 += value,
-            NavInstruction::MoveSouth(value) => self.waypoint.0 -= value,
-            NavInstruction::MoveEast(value) => self.waypoint.1 += value,
-            NavInstruction::MoveWest(value) => self.waypoint.1 -= value,
-            NavInstruction::MoveForward(value) => {
-                self.position.0 += value * self.waypoint.0;
-                self.position.1 += value * self.waypoint.1;
+            NavInstruction::MoveNorth(value) => {
+                self.waypoint.0 = moved(self.waypoint.0, Some(value))?
+            }
+            NavInstruction::MoveSouth(value) => {
+                self.waypoint.0 = moved(self.waypoint.0, value.checked_neg())?
             }
-            NavInstruction::TurnRight(value) => {
-                let mut degrees = value;
-                while degrees > 0 {
-                    self.waypoint = (-self.waypoint.1, self.waypoint.0);
-                    degrees -= 90;
-                }
+            NavInstruction::MoveEast(value) => {
+                self.waypoint.1 = moved(self.waypoint.1, Some(value))?
             }
-            NavInstruction::TurnLeft(value) => {
-                let mut degrees = value;
-                while degrees > 0 {
-                    self.waypoint = (self.waypoint.1, -self.waypoint.0);
-                    degrees -= 90;
-                }
+            NavInstruction::MoveWest(value) => {
+                self.waypoint.1 = moved(self.waypoint.1, value.checked_neg())?
             }
+            NavInstruction::MoveForward(value) => {
+                self.position.0 = moved(self.position.0, value.checked_mul(self.waypoint.0))?;
+                self.position.1 = moved(self.position.1, value.checked_mul(self.waypoint.1))?;
+            }
+            NavInstruction::TurnRight(value) => self.turn(quarter_turns(value)?),
+            NavInstruction::TurnLeft(value) => self.turn((4 - quarter_turns(value)?) % 4),
         }
+
+        Ok(())
     }
 }
 
-fn part1(instructions: &[NavInstruction]) -> AppResult<()> {
-    let ship = instructions.iter().fold(
-        DirectionalShip::new(Direction::East),
-        |mut ship, instruction| {
-            (&mut ship).process_instruction(instruction);
-            ship
-        },
-    );
-
-    println!(
-        "Part 1: \"{:?}\"",
-        ship.position.0.abs() + ship.position.1.abs()
-    );
-
-    Ok(())
-}
-
-fn part2(instructions: &[NavInstruction]) -> AppResult<()> {
-    let ship = instructions
-        .iter()
-        .fold(WaypointShip::new(), |mut ship, instruction| {
-            (&mut ship).process_instruction(instruction);
-            ship
-        });
-
-    println!(
-        "Part 2: \"{:?}\"",
-        ship.position.0.abs() + ship.position.1.abs()
-    );
-
-    Ok(())
-}
-
-pub fn run() -> AppResult<()> {
-    let instructions = INPUT
-        .split("\n")
-        .map(|line| {
-            let instruction = &line[0..1];
-            let value = line[1..].parse::<i32>().expect("Missing instruction value");
-
-            match instruction {
-                "N" => NavInstruction::MoveNorth(value),
-                "E" => NavInstruction::MoveEast(value),
-                "S" => NavInstruction::MoveSouth(value),
-                "W" => NavInstruction::MoveWest(value),
-                "F" => NavInstruction::MoveForward(value),
-                "L" => NavInstruction::TurnLeft(value),
-                "R" => NavInstruction::TurnRight(value),
-                value => panic!("Unexpected value \"{}\"", value),
-            }
-        })
-        .collect::<Vec<_>>();
+/// Moves a ship facing east and returns the Manhattan distance it travelled.
+///
+/// Fails on turns that aren't a multiple of 90 degrees, or when the ship
+/// sails further than an i32 can track.
+pub fn navigate_directional_ship(instructions: &[NavInstruction]) -> AppResult<i32> {
+    let mut ship = DirectionalShip::new(Direction::East);
+    for instruction in instructions {
+        ship.process_instruction(instruction)?;
+    }
+
+    manhattan_distance(ship.position)
+}
+
+/// Moves a ship towards a waypoint that starts 10 east and 1 north of it, and
+/// returns the Manhattan distance it travelled.
+///
+/// Fails like [`navigate_directional_ship`].
+pub fn navigate_waypoint_ship(instructions: &[NavInstruction]) -> AppResult<i32> {
+    let mut ship = WaypointShip::new();
+    for instruction in instructions {
+        ship.process_instruction(instruction)?;
+    }
+
+    manhattan_distance(ship.position)
+}
 
-    part1(&instructions)?;
+/// Parses an instruction like `F10` or `R90`.
+pub fn parse_nav_instruction(line_number: usize, line: &str) -> AppResult<NavInstruction> {
+    let instruction = line
+        .chars()
+        .next()
+        .ok_or_else(|| AppError::parse(line_number, 1, "Missing instruction"))?;
 
-    part2(&instructions)?;
+    let value = &line[instruction.len_utf8()..];
+    let value = value.parse::<i32>().map_err(|err| {
+        ParseError::at_token(line_number, line, value, "Missing instruction value").with_source(err)
+    })?;
 
-    Ok(())
+    let is_turn = instruction == 'L' || instruction == 'R';
+    if is_turn && (value < 0 || value % 90 != 0) {
+        return Err(AppError::parse(
+            line_number,
+            2,
+            &format!("Turns must be a multiple of 90 degrees, found {}", value),
+        ));
+    }
+
+    match instruction {
+        'N' => Ok(NavInstruction::MoveNorth(value)),
+        'E' => Ok(NavInstruction::MoveEast(value)),
+        'S' => Ok(NavInstruction::MoveSouth(value)),
+        'W' => Ok(NavInstruction::MoveWest(value)),
+        'F' => Ok(NavInstruction::MoveForward(value)),
+        'L' => Ok(NavInstruction::TurnLeft(value)),
+        'R' => Ok(NavInstruction::TurnRight(value)),
+        value => Err(AppError::parse(
+            line_number,
+            1,
+            &format!("Unexpected value \"{}\"", value),
+        )),
+    }
+}
+
+/// Day 12: Rain Risk.
+pub struct RainRisk;
+
+impl Solution for RainRisk {
+    type Input = Vec<NavInstruction>;
+    type Answer = i32;
+
+    fn parse(&self, input: &str) -> AppResult<Self::Input> {
+        input
+            .split('\n')
+            .enumerate()
+            .map(|(index, line)| parse_nav_instruction(index + 1, line))
+            .collect()
+    }
+
+    fn part1(&self, instructions: &Self::Input) -> AppResult<Self::Answer> {
+        navigate_directional_ship(instructions)
+    }
+
+    fn part2(&self, instructions: &Self::Input) -> AppResult<Self::Answer> {
+        navigate_waypoint_ship(instructions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "F10\nN3\nF7\nR90\nF11";
+
+    #[test]
+    fn part1_example() {
+        let instructions = RainRisk.parse(EXAMPLE).unwrap();
+
+        assert_eq!(RainRisk.part1(&instructions).unwrap(), 25);
+    }
+
+    #[test]
+    fn part2_example() {
+        let instructions = RainRisk.parse(EXAMPLE).unwrap();
+
+        assert_eq!(RainRisk.part2(&instructions).unwrap(), 286);
+    }
+
+    #[test]
+    fn turns_must_be_right_angles() {
+        assert!(RainRisk.parse("R45").is_err());
+    }
+
+    #[test]
+    fn full_turns() {
+        let instructions = RainRisk.parse("L720\nF10\nR450\nF3").unwrap();
+
+        assert_eq!(RainRisk.part1(&instructions).unwrap(), 13);
+        assert_eq!(RainRisk.part2(&instructions).unwrap(), 123);
+    }
+
+    #[test]
+    fn turns_built_by_hand() {
+        let instructions = [
+            NavInstruction::TurnLeft(720),
+            NavInstruction::MoveForward(10),
+            NavInstruction::TurnRight(-270),
+            NavInstruction::MoveForward(3),
+        ];
+        assert_eq!(navigate_directional_ship(&instructions).unwrap(), 13);
+        assert_eq!(navigate_waypoint_ship(&instructions).unwrap(), 123);
+
+        let instructions = [NavInstruction::TurnRight(45)];
+        assert!(navigate_directional_ship(&instructions).is_err());
+        assert!(navigate_waypoint_ship(&instructions).is_err());
+    }
+
+    #[test]
+    fn sailing_too_far() {
+        let instructions = RainRisk.parse("F2147483647\nF2147483647").unwrap();
+
+        assert!(RainRisk.part1(&instructions).is_err());
+        assert!(RainRisk.part2(&instructions).is_err());
+    }
 }
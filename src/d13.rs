@@ -1,19 +1,46 @@
-use crate::errors::AppResult;
+use crate::errors::{AppError, AppResult, ParseError};
+use crate::solution::Solution;
+
+/// Finds the first bus leaving at or after `departure_timestamp`, returning
+/// its id and departure time. `None` entries are out of service.
+pub fn find_earliest_bus(
+    departure_timestamp: u32,
+    bus_schedule: &[Option<u32>],
+) -> Option<(u32, u64)> {
+    // in u64, as the next departure after a timestamp near `u32::MAX` may not
+    // fit in a u32
+    let departure_timestamp = departure_timestamp as u64;
 
-const INPUT: &'static str = include_str!("../data/bus-notes.txt");
-
-fn find_earliest_bus(departure_timestamp: u32, bus_schedule: &[Option<u32>]) -> (u32, u32) {
     bus_schedule
         .iter()
         .filter_map(|&id| id)
-        .map(|id| (id, (departure_timestamp - (departure_timestamp % id)) + id))
-        .fold((u32::MAX, u32::MAX), |min, (id, time)| match time < min.1 {
-            true => (id, time),
-            false => min,
+        .map(|id| (id, id as u64))
+        .map(|(id, period)| {
+            (
+                id,
+                departure_timestamp - (departure_timestamp % period) + period,
+            )
         })
+        .min_by_key(|&(_, time)| time)
 }
 
-fn find_sequential_bus_schedule(bus_schedule: &[Option<u64>]) -> u64 {
+fn gcd(a: u64, b: u64) -> u64 {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+fn overflow() -> AppError {
+    AppError::unsolvable("Bus schedule overflows a 64-bit timestamp")
+}
+
+/// Finds the earliest timestamp at which each bus departs as many minutes
+/// after it as its index in `bus_schedule`.
+///
+/// Fails with [`AppError::Unsolvable`] when no timestamp works, which can
+/// happen when bus IDs share a factor, or when it doesn't fit in a u64.
+pub fn find_sequential_bus_schedule(bus_schedule: &[Option<u64>]) -> AppResult<u64> {
     let mut bus_sequence = bus_schedule
         .iter()
         .enumerate()
@@ -23,62 +50,182 @@ fn find_sequential_bus_schedule(bus_schedule: &[Option<u64>]) -> u64 {
     bus_sequence.sort_by(|(_, a), (_, b)| b.cmp(a));
 
     let mut current_index = 1;
-    let (offset, mut step) = bus_sequence
-        .get(0)
-        .expect("Bus schedule must have at least 1 entry");
+    let (offset, mut step) = *bus_sequence
+        .first()
+        .ok_or_else(|| AppError::invalid_input("Bus schedule must have at least 1 entry"))?;
+
+    let mut time = step - offset % step;
+
+    while current_index < bus_sequence.len() {
+        let (i, x) = bus_sequence[current_index];
+
+        // stepping by `step` only reaches `x / gcd` distinct remainders
+        // modulo `x`, so if none of them fit, no time ever will
+        let attempts = x / gcd(step, x);
+        let mut attempt = 0;
+        while time.checked_add(i).ok_or_else(overflow)? % x != 0 {
+            attempt += 1;
+            if attempt == attempts {
+                return Err(AppError::unsolvable(&format!(
+                    "No timestamp fits bus {} at offset {}",
+                    x, i
+                )));
+            }
+
+            time = time.checked_add(step).ok_or_else(overflow)?;
+        }
+
+        current_index += 1;
+        step = (step / gcd(step, x)).checked_mul(x).ok_or_else(overflow)?;
+    }
 
-    let mut time = step - offset;
+    Ok(time)
+}
 
-    loop {
-        let (i, x) = bus_sequence
-            .get(current_index)
-            .expect("Out of bounds when getting next bus");
+/// The earliest time we can depart, and the bus ids with `None` for buses out
+/// of service.
+#[derive(Debug)]
+pub struct BusNotes {
+    pub departure_timestamp: u32,
+    pub bus_schedule: Vec<Option<u32>>,
+}
 
-        if (time + i) % x == 0 {
-            current_index += 1;
-            step *= x;
-        }
+/// Day 13: Shuttle Search.
+pub struct ShuttleSearch;
+
+impl Solution for ShuttleSearch {
+    type Input = BusNotes;
+    type Answer = u64;
+
+    fn parse(&self, input: &str) -> AppResult<Self::Input> {
+        let bus_notes = input.split('\n').collect::<Vec<_>>();
+        let departure_timestamp = bus_notes
+            .first()
+            .ok_or_else(|| AppError::parse(1, 1, "Missing depature timestamp"))?;
+        let departure_timestamp = departure_timestamp.parse::<u32>().map_err(|err| {
+            ParseError::new(1, 1, "Unable to parse depature timestamp").with_source(err)
+        })?;
+
+        let bus_ids = bus_notes
+            .get(1)
+            .ok_or_else(|| AppError::parse(2, 1, "Missing bus IDs"))?;
+        let bus_schedule = bus_ids
+            .split(',')
+            .map(|id| match id {
+                "x" => Ok(None),
+                _ => match id.parse::<u32>() {
+                    Ok(0) => {
+                        Err(ParseError::at_token(2, bus_ids, id, "Bus IDs must be positive").into())
+                    }
+                    Ok(id) => Ok(Some(id)),
+                    Err(err) => {
+                        Err(
+                            ParseError::at_token(2, bus_ids, id, "Unable to parse ID as integer")
+                                .with_source(err)
+                                .into(),
+                        )
+                    }
+                },
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(BusNotes {
+            departure_timestamp,
+            bus_schedule,
+        })
+    }
 
-        if current_index == bus_sequence.len() {
-            return time;
-        }
+    fn part1(&self, notes: &Self::Input) -> AppResult<Self::Answer> {
+        let (earliest_id, earliest_time) =
+            find_earliest_bus(notes.departure_timestamp, &notes.bus_schedule)
+                .ok_or_else(|| AppError::invalid_input("Bus schedule has no bus IDs"))?;
+
+        Ok((earliest_time - notes.departure_timestamp as u64) * earliest_id as u64)
+    }
 
-        time += step;
+    fn part2(&self, notes: &Self::Input) -> AppResult<Self::Answer> {
+        find_sequential_bus_schedule(
+            &notes
+                .bus_schedule
+                .iter()
+                .map(|x| x.map(|i| i as u64))
+                .collect::<Vec<_>>(),
+        )
     }
 }
 
-pub fn run() -> AppResult<()> {
-    let bus_notes = INPUT.split("\n").collect::<Vec<_>>();
-    let departure_timestamp = bus_notes
-        .get(0)
-        .expect("Missing depature timestamp")
-        .parse::<u32>()
-        .expect("Unable to parse depature timestamp");
-
-    let bus_schedule = bus_notes
-        .get(1)
-        .expect("Missing bus IDs")
-        .split(",")
-        .map(|id| match id {
-            "x" => None,
-            _ => Some(id.parse::<u32>().expect("Unable to parse ID as integer")),
-        })
-        .collect::<Vec<_>>();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "939\n7,13,x,x,59,x,31,19";
+
+    #[test]
+    fn part1_example() {
+        let notes = ShuttleSearch.parse(EXAMPLE).unwrap();
+
+        assert_eq!(ShuttleSearch.part1(&notes).unwrap(), 295);
+    }
+
+    #[test]
+    fn part2_examples() {
+        let examples = [
+            ("7,13,x,x,59,x,31,19", 1068781),
+            ("17,x,13,19", 3417),
+            ("67,7,59,61", 754018),
+            ("67,x,7,59,61", 779210),
+            ("67,7,x,59,61", 1261476),
+            ("1789,37,47,1889", 1202161486),
+        ];
+
+        for (schedule, expected) in examples.iter() {
+            let notes = ShuttleSearch.parse(&format!("0\n{}", schedule)).unwrap();
+
+            assert_eq!(
+                ShuttleSearch.part2(&notes).unwrap(),
+                *expected,
+                "{}",
+                schedule
+            );
+        }
+    }
+
+    #[test]
+    fn single_bus() {
+        assert_eq!(find_sequential_bus_schedule(&[Some(7)]).unwrap(), 7);
+    }
+
+    #[test]
+    fn shared_factors() {
+        let notes = ShuttleSearch.parse("0\n4,6").unwrap();
+        assert!(matches!(
+            ShuttleSearch.part2(&notes),
+            Err(AppError::Unsolvable(_))
+        ));
 
-    let (earliest_id, earliest_time) = find_earliest_bus(departure_timestamp, &bus_schedule);
-    println!(
-        "Part 1: \"{}\"",
-        (earliest_time - departure_timestamp) * earliest_id
-    );
+        let notes = ShuttleSearch.parse("0\n4,x,6").unwrap();
+        assert_eq!(ShuttleSearch.part2(&notes).unwrap(), 4);
+    }
 
-    let sequential_schedule_start_time = find_sequential_bus_schedule(
-        &bus_schedule
-            .iter()
-            .map(|x| x.map(|i| i as u64))
-            .collect::<Vec<_>>(),
-    );
+    #[test]
+    fn offset_past_the_id() {
+        let notes = ShuttleSearch.parse("0\nx,x,x,x,x,3").unwrap();
+
+        assert_eq!(ShuttleSearch.part2(&notes).unwrap(), 1);
+    }
 
-    println!("Part 2: \"{}\"", sequential_schedule_start_time);
+    #[test]
+    fn departure_near_the_limit() {
+        let notes = ShuttleSearch.parse("4294967295\n7").unwrap();
 
-    Ok(())
+        assert_eq!(ShuttleSearch.part1(&notes).unwrap(), 7 * 4);
+    }
+
+    #[test]
+    fn no_buses() {
+        let notes = ShuttleSearch.parse("939\nx,x").unwrap();
+
+        assert!(ShuttleSearch.part1(&notes).is_err());
+        assert!(ShuttleSearch.part2(&notes).is_err());
+    }
 }
@@ -1,7 +1,263 @@
-use crate::errors::AppResult;
+use regex::Regex;
+use std::collections::HashMap;
 
-const INPUT: &'static str = include_str!("../data/docking-program.txt");
+use crate::errors::{AppError, AppResult, ParseError};
+use crate::solution::Solution;
 
-pub fn run() -> AppResult<()> {
-    Ok(())
+lazy_static! {
+    static ref MEM_REGEX: Regex = Regex::new(r"^mem\[(\d+)\] = (\d+)$").unwrap();
+}
+
+pub const MASK_WIDTH: usize = 36;
+
+/// The most floating bits an address mask may have, as every address it
+/// decodes to is written. Puzzle inputs have at most 9.
+pub const MAX_FLOATING_BITS: u32 = 16;
+
+/// A mask of `MASK_WIDTH` bits, each either forced to `1`, forced to `0` or
+/// left floating by `X`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Bitmask {
+    ones: u64,
+    zeros: u64,
+    floating: u64,
+}
+
+impl Bitmask {
+    // `value` must be a slice of `line` so errors can point at the bad bit.
+    pub fn parse(line_number: usize, line: &str, value: &str) -> AppResult<Self> {
+        if value.len() != MASK_WIDTH {
+            return Err(ParseError::at_token(
+                line_number,
+                line,
+                value,
+                &format!("Expected mask of {} bits, found \"{}\"", MASK_WIDTH, value),
+            )
+            .into());
+        }
+
+        value.char_indices().rev().enumerate().try_fold(
+            Bitmask::default(),
+            |mut mask, (bit, (index, character))| {
+                match character {
+                    '1' => mask.ones |= 1 << bit,
+                    '0' => mask.zeros |= 1 << bit,
+                    'X' => mask.floating |= 1 << bit,
+                    character => {
+                        return Err(ParseError::at_token(
+                            line_number,
+                            line,
+                            &value[index..],
+                            &format!("Unknown value \"{}\" in mask", character),
+                        )
+                        .into())
+                    }
+                };
+
+                Ok(mask)
+            },
+        )
+    }
+
+    /// Overwrites the bits of `value` with the mask's `1`s and `0`s.
+    pub fn apply_to_value(&self, value: u64) -> u64 {
+        (value | self.ones) & !self.zeros
+    }
+
+    /// Sets the mask's `1`s in `address` and expands every floating bit into
+    /// both of its values. Fails when the mask has more than
+    /// [`MAX_FLOATING_BITS`] floating bits.
+    pub fn apply_to_address(&self, address: u64) -> AppResult<Vec<u64>> {
+        if self.floating.count_ones() > MAX_FLOATING_BITS {
+            return Err(AppError::unsolvable(&format!(
+                "Mask has {} floating bits, at most {} are supported",
+                self.floating.count_ones(),
+                MAX_FLOATING_BITS
+            )));
+        }
+
+        let base = (address | self.ones) & !self.floating;
+        let floating_bits = (0..MASK_WIDTH)
+            .map(|bit| 1 << bit)
+            .filter(|bit| self.floating & bit != 0)
+            .collect::<Vec<u64>>();
+
+        Ok((0..(1u64 << floating_bits.len()))
+            .map(|combination| {
+                floating_bits
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| combination & (1 << index) != 0)
+                    .fold(base, |acc, (_, bit)| acc | bit)
+            })
+            .collect())
+    }
+}
+
+/// A line of the initialization program.
+#[derive(Debug)]
+pub enum Instruction {
+    SetMask(Bitmask),
+    Write { address: u64, value: u64 },
+}
+
+/// Whether the mask applies to the values written, or to the addresses they're
+/// written to.
+#[derive(Debug, Clone, Copy)]
+pub enum DecoderChip {
+    Value,
+    Address,
+}
+
+/// The memory written by a program.
+#[derive(Debug, Default)]
+pub struct Memory {
+    cells: HashMap<u64, u64>,
+}
+
+impl Memory {
+    pub fn write(&mut self, address: u64, value: u64) {
+        self.cells.insert(address, value);
+    }
+
+    pub fn sum(&self) -> u64 {
+        self.cells.values().sum()
+    }
+}
+
+fn parse_number(line_number: usize, line: &str, value: &str) -> AppResult<u64> {
+    value.parse::<u64>().map_err(|err| {
+        ParseError::at_token(line_number, line, value, "Invalid number")
+            .with_source(err)
+            .into()
+    })
+}
+
+/// Parses a line like `mask = X1` or `mem[8] = 11`.
+pub fn parse_instruction(line_number: usize, line: &str) -> AppResult<Instruction> {
+    if let Some(mask) = line.strip_prefix("mask = ") {
+        return Ok(Instruction::SetMask(Bitmask::parse(
+            line_number,
+            line,
+            mask,
+        )?));
+    }
+
+    let captures = MEM_REGEX.captures(line).ok_or_else(|| {
+        AppError::parse(line_number, 1, &format!("Unknown instruction \"{}\"", line))
+    })?;
+
+    Ok(Instruction::Write {
+        address: parse_number(line_number, line, captures.get(1).unwrap().as_str())?,
+        value: parse_number(line_number, line, captures.get(2).unwrap().as_str())?,
+    })
+}
+
+/// Runs `instructions` from an empty memory.
+pub fn run_program(instructions: &[Instruction], chip: DecoderChip) -> AppResult<Memory> {
+    let mut memory = Memory::default();
+    let mut mask = Bitmask::default();
+
+    for instruction in instructions {
+        match (instruction, chip) {
+            (Instruction::SetMask(value), _) => mask = *value,
+            (&Instruction::Write { address, value }, DecoderChip::Value) => {
+                memory.write(address, mask.apply_to_value(value))
+            }
+            (&Instruction::Write { address, value }, DecoderChip::Address) => {
+                for decoded in mask.apply_to_address(address)? {
+                    memory.write(decoded, value);
+                }
+            }
+        }
+    }
+
+    Ok(memory)
+}
+
+/// Day 14: Docking Data.
+pub struct DockingData;
+
+impl Solution for DockingData {
+    type Input = Vec<Instruction>;
+    type Answer = u64;
+
+    fn parse(&self, input: &str) -> AppResult<Self::Input> {
+        input
+            .split('\n')
+            .enumerate()
+            .map(|(index, line)| parse_instruction(index + 1, line))
+            .collect()
+    }
+
+    fn part1(&self, instructions: &Self::Input) -> AppResult<Self::Answer> {
+        Ok(run_program(instructions, DecoderChip::Value)?.sum())
+    }
+
+    fn part2(&self, instructions: &Self::Input) -> AppResult<Self::Answer> {
+        Ok(run_program(instructions, DecoderChip::Address)?.sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part1_example() {
+        let instructions = DockingData
+            .parse("mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X\nmem[8] = 11\nmem[7] = 101\nmem[8] = 0")
+            .unwrap();
+
+        assert_eq!(DockingData.part1(&instructions).unwrap(), 165);
+    }
+
+    #[test]
+    fn part2_example() {
+        let instructions = DockingData
+            .parse(
+                "\
+mask = 000000000000000000000000000000X1001X
+mem[42] = 100
+mask = 00000000000000000000000000000000X0XX
+mem[26] = 1",
+            )
+            .unwrap();
+
+        assert_eq!(DockingData.part2(&instructions).unwrap(), 208);
+    }
+
+    #[test]
+    fn floating_address_bits() {
+        let mask = Bitmask::parse(
+            1,
+            "000000000000000000000000000000X1001X",
+            "000000000000000000000000000000X1001X",
+        )
+        .unwrap();
+        let mut addresses = mask.apply_to_address(42).unwrap();
+        addresses.sort_unstable();
+
+        assert_eq!(addresses, vec![26, 27, 58, 59]);
+    }
+
+    #[test]
+    fn too_many_floating_bits() {
+        let instructions = DockingData
+            .parse("mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX\nmem[8] = 11")
+            .unwrap();
+
+        assert_eq!(DockingData.part1(&instructions).unwrap(), 11);
+        assert!(matches!(
+            DockingData.part2(&instructions),
+            Err(AppError::Unsolvable(_))
+        ));
+    }
+
+    #[test]
+    fn unknown_mask_bit() {
+        assert!(DockingData
+            .parse("mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0Y")
+            .is_err());
+    }
 }
@@ -1,38 +1,195 @@
+use std::error::Error;
 use std::fmt;
-use std::num::ParseIntError;
+use std::io;
 
+/// A parse error pointing at a 1-based line and column of the input.
 #[derive(Debug)]
-pub struct AppError {
-    message: String,
+pub struct ParseError {
+    pub file: Option<String>,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, message: &str) -> Self {
+        Self {
+            file: None,
+            line,
+            column,
+            message: message.to_string(),
+            source: None,
+        }
+    }
+
+    // Finds the 1-based line and column of a byte `offset` into `input`.
+    pub fn at_offset(input: &str, offset: usize, message: &str) -> Self {
+        let before = &input[..offset.min(input.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = match before.rfind('\n') {
+            Some(index) => before[(index + 1)..].chars().count() + 1,
+            None => before.chars().count() + 1,
+        };
+
+        Self::new(line, column, message)
+    }
+
+    // Finds the 1-based line and column of `slice`, which must be a slice of
+    // `input`.
+    pub fn at_slice(input: &str, slice: &str, message: &str) -> Self {
+        let offset = (slice.as_ptr() as usize).saturating_sub(input.as_ptr() as usize);
+
+        Self::at_offset(input, offset, message)
+    }
+
+    // Finds the 1-based column of `token`, which must be a slice of `line`.
+    pub fn at_token(line_number: usize, line: &str, token: &str, message: &str) -> Self {
+        let offset = (token.as_ptr() as usize).saturating_sub(line.as_ptr() as usize);
+        let column = line[..offset.min(line.len())].chars().count() + 1;
+
+        Self::new(line_number, column, message)
+    }
+
+    pub fn with_source<E>(mut self, source: E) -> Self
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        self.source = Some(Box::new(source));
+        self
+    }
+
+    pub fn in_file(self, file: &str) -> Self {
+        ParseError {
+            file: Some(file.to_string()),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(
+                f,
+                "{}:{}:{}: {}",
+                file, self.line, self.column, self.message
+            ),
+            None => write!(
+                f,
+                "line {}, column {}: {}",
+                self.line, self.column, self.message
+            ),
+        }
+    }
+}
+
+/// Every error the solutions and the CLI can produce.
+#[derive(Debug)]
+pub enum AppError {
+    Io {
+        context: Option<String>,
+        source: io::Error,
+    },
+    Parse(ParseError),
+    // every invalid line of an input, for parsers that keep going after the
+    // first one
+    Diagnostics(Vec<ParseError>),
+    InvalidInput(String),
+    Unsolvable(String),
 }
 
 impl AppError {
-    #[allow(dead_code)]
-    pub fn new(msg: &str) -> AppError {
-        AppError {
-            message: msg.to_string(),
+    pub fn io(context: &str, source: io::Error) -> Self {
+        AppError::Io {
+            context: Some(context.to_string()),
+            source,
+        }
+    }
+
+    pub fn parse(line: usize, column: usize, message: &str) -> Self {
+        AppError::Parse(ParseError::new(line, column, message))
+    }
+
+    /// Wraps the errors of every invalid line, unwrapping a lone error.
+    pub fn diagnostics(mut errors: Vec<ParseError>) -> Self {
+        match errors.len() {
+            1 => AppError::Parse(errors.remove(0)),
+            _ => AppError::Diagnostics(errors),
+        }
+    }
+
+    pub fn invalid_input(message: &str) -> Self {
+        AppError::InvalidInput(message.to_string())
+    }
+
+    pub fn unsolvable(message: &str) -> Self {
+        AppError::Unsolvable(message.to_string())
+    }
+
+    // Parsers only see the input text, so the name of the file it came from is
+    // attached afterwards by whoever loaded it.
+    pub fn in_file(self, file: &str) -> Self {
+        match self {
+            AppError::Parse(err) => AppError::Parse(err.in_file(file)),
+            AppError::Diagnostics(errors) => {
+                AppError::Diagnostics(errors.into_iter().map(|err| err.in_file(file)).collect())
+            }
+            err => err,
         }
     }
 }
 
 impl fmt::Display for AppError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.message)
+        match self {
+            AppError::Io {
+                context: Some(context),
+                ..
+            } => write!(f, "{}", context),
+            AppError::Io { source, .. } => write!(f, "{}", source),
+            AppError::Parse(err) => write!(f, "Parse error at {}", err),
+            AppError::Diagnostics(errors) => {
+                write!(f, "{} parse error(s)", errors.len())?;
+                for err in errors {
+                    write!(f, "\n  {}", err)?;
+                }
+
+                Ok(())
+            }
+            AppError::InvalidInput(message) => write!(f, "{}", message),
+            AppError::Unsolvable(message) => write!(f, "Unsolvable: {}", message),
+        }
     }
 }
 
-impl From<ParseIntError> for AppError {
-    fn from(err: ParseIntError) -> Self {
-        AppError {
-            message: err.to_string(),
+impl Error for AppError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AppError::Io {
+                context: Some(_),
+                source,
+            } => Some(source),
+            AppError::Parse(ParseError {
+                source: Some(source),
+                ..
+            }) => Some(source.as_ref()),
+            _ => None,
         }
     }
 }
 
-impl From<std::io::Error> for AppError {
-    fn from(err: std::io::Error) -> Self {
-        AppError {
-            message: err.to_string(),
+impl From<ParseError> for AppError {
+    fn from(err: ParseError) -> Self {
+        AppError::Parse(err)
+    }
+}
+
+impl From<io::Error> for AppError {
+    fn from(err: io::Error) -> Self {
+        AppError::Io {
+            context: None,
+            source: err,
         }
     }
 }
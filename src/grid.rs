@@ -0,0 +1,291 @@
+use crate::errors::{AppError, AppResult};
+
+/// Cells that can be read from a single character of a map.
+pub trait FromChar: Sized {
+    fn from_char(value: char) -> Option<Self>;
+}
+
+// the `.`/`#` format, where `#` marks an obstacle
+impl FromChar for bool {
+    fn from_char(value: char) -> Option<Self> {
+        match value {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        }
+    }
+}
+
+impl FromChar for char {
+    fn from_char(value: char) -> Option<Self> {
+        Some(value)
+    }
+}
+
+/// A rectangular tile of cells that repeats forever in every direction.
+///
+/// Positions are `(x, y)`, with `x` counting columns to the right and `y`
+/// rows down from the top left corner. [`at`](TilingGrid::at) wraps any
+/// position onto the tile, while [`get`](TilingGrid::get) only sees the tile
+/// itself, for grids that don't repeat.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TilingGrid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> TilingGrid<T> {
+    /// Builds a grid from its rows, which must all be the same, non-zero,
+    /// width.
+    pub fn new(rows: Vec<Vec<T>>) -> AppResult<Self> {
+        let height = rows.len();
+        let width = rows
+            .first()
+            .ok_or_else(|| AppError::invalid_input("Grid must contain at least 1 row"))?
+            .len();
+
+        if width == 0 {
+            return Err(AppError::parse(
+                1,
+                1,
+                "Grid rows must contain at least 1 cell",
+            ));
+        }
+
+        if let Some((row, cells)) = rows.iter().enumerate().find(|(_, row)| row.len() != width) {
+            return Err(AppError::parse(
+                row + 1,
+                cells.len().min(width) + 1,
+                &format!("Expected {} cells, found {}", width, cells.len()),
+            ));
+        }
+
+        Ok(TilingGrid {
+            cells: rows.into_iter().flatten().collect(),
+            width,
+            height,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether `position` lies on the tile itself rather than a repeat of it.
+    pub fn contains(&self, (x, y): (isize, isize)) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    /// The cell at `position` on the tile itself, without wrapping.
+    pub fn get(&self, (x, y): (usize, usize)) -> Option<&T> {
+        match x < self.width && y < self.height {
+            true => self.cells.get(y * self.width + x),
+            false => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, (x, y): (usize, usize)) -> Option<&mut T> {
+        match x < self.width && y < self.height {
+            true => self.cells.get_mut(y * self.width + x),
+            false => None,
+        }
+    }
+
+    fn wrap(&self, (x, y): (isize, isize)) -> usize {
+        // most lookups land on the tile, and skipping the divisions for them
+        // is what keeps walking a slope cheap
+        match self.contains((x, y)) {
+            true => y as usize * self.width + x as usize,
+            false => {
+                let x = x.rem_euclid(self.width as isize) as usize;
+                let y = y.rem_euclid(self.height as isize) as usize;
+
+                y * self.width + x
+            }
+        }
+    }
+
+    /// The cell at `position`, wrapping around both axes.
+    pub fn at(&self, position: (isize, isize)) -> &T {
+        &self.cells[self.wrap(position)]
+    }
+
+    pub fn at_mut(&mut self, position: (isize, isize)) -> &mut T {
+        let index = self.wrap(position);
+
+        &mut self.cells[index]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width)
+    }
+
+    /// Every cell of the tile along with its position, row by row.
+    pub fn cells(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let width = self.width;
+
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(index, cell)| ((index % width, index / width), cell))
+    }
+
+    /// Walks from `start` by `step` forever, yielding each position visited
+    /// after `start` along with its cell. Positions aren't wrapped, so the
+    /// walk can be cut short with [`contains`](TilingGrid::contains) or by
+    /// comparing against the height.
+    pub fn slope(&self, start: (isize, isize), step: (isize, isize)) -> SlopeIter<'_, T> {
+        SlopeIter {
+            grid: self,
+            position: start,
+            step,
+        }
+    }
+
+    /// Like [`slope`](TilingGrid::slope), but stops at the edge of the tile
+    /// instead of wrapping around it.
+    pub fn ray(&self, start: (usize, usize), step: (isize, isize)) -> RayIter<'_, T> {
+        RayIter {
+            grid: self,
+            position: start,
+            step,
+        }
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> TilingGrid<U> {
+        TilingGrid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<T: FromChar> TilingGrid<T> {
+    /// Parses a map with a row per line and a cell per character.
+    pub fn parse(input: &str) -> AppResult<Self> {
+        let rows = input
+            .split('\n')
+            .enumerate()
+            .map(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(|(column, character)| {
+                        T::from_char(character).ok_or_else(|| {
+                            AppError::parse(
+                                row + 1,
+                                column + 1,
+                                &format!("Unknown value \"{}\" in map", character),
+                            )
+                        })
+                    })
+                    .collect::<AppResult<Vec<_>>>()
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        TilingGrid::new(rows)
+    }
+}
+
+/// Iterator over the cells along a slope, see [`TilingGrid::slope`].
+#[derive(Debug)]
+pub struct SlopeIter<'a, T> {
+    grid: &'a TilingGrid<T>,
+    position: (isize, isize),
+    step: (isize, isize),
+}
+
+impl<'a, T> Iterator for SlopeIter<'a, T> {
+    type Item = ((isize, isize), &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.position = (self.position.0 + self.step.0, self.position.1 + self.step.1);
+
+        Some((self.position, self.grid.at(self.position)))
+    }
+}
+
+/// Iterator over the cells along a slope up to the edge of the tile, see
+/// [`TilingGrid::ray`].
+#[derive(Debug)]
+pub struct RayIter<'a, T> {
+    grid: &'a TilingGrid<T>,
+    position: (usize, usize),
+    step: (isize, isize),
+}
+
+impl<'a, T> Iterator for RayIter<'a, T> {
+    type Item = ((usize, usize), &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.position = (
+            self.position.0.checked_add_signed(self.step.0)?,
+            self.position.1.checked_add_signed(self.step.1)?,
+        );
+
+        Some((self.position, self.grid.get(self.position)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_both_axes() {
+        let grid = TilingGrid::<char>::parse("ab\ncd\nef").unwrap();
+
+        assert_eq!(*grid.at((2, 0)), 'a');
+        assert_eq!(*grid.at((-1, -1)), 'f');
+        assert_eq!(*grid.at((3, 4)), 'd');
+        assert_eq!(grid.get((2, 0)), None);
+    }
+
+    #[test]
+    fn slope_skips_the_start() {
+        let grid = TilingGrid::<bool>::parse("#..\n.#.\n..#").unwrap();
+        let cells = grid
+            .slope((0, 0), (1, 1))
+            .take(4)
+            .map(|(position, &tree)| (position, tree))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            cells,
+            vec![
+                ((1, 1), true),
+                ((2, 2), true),
+                ((3, 3), true),
+                ((4, 4), true)
+            ]
+        );
+    }
+
+    #[test]
+    fn ray_stops_at_the_edge() {
+        let grid = TilingGrid::<char>::parse("abc\ndef\nghi").unwrap();
+        let cells = |start, step| {
+            grid.ray(start, step)
+                .map(|(_, &cell)| cell)
+                .collect::<String>()
+        };
+
+        assert_eq!(cells((0, 0), (1, 1)), "ei");
+        assert_eq!(cells((2, 1), (-1, 0)), "ed");
+        assert_eq!(cells((1, 1), (0, -1)), "b");
+        assert_eq!(cells((2, 2), (1, 0)), "");
+    }
+
+    #[test]
+    fn unknown_cell() {
+        match TilingGrid::<bool>::parse("..\n.x") {
+            Err(AppError::Parse(err)) => assert_eq!((err.line, err.column), (2, 2)),
+            result => panic!("Expected a parse error, found {:?}", result),
+        }
+    }
+}
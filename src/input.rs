@@ -0,0 +1,130 @@
+use flate2::bufread::MultiGzDecoder;
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::errors::{AppError, AppResult};
+
+pub const INPUT_ENV_VAR: &str = "AOC_INPUT";
+
+pub const DATA_DIR_ENV_VAR: &str = "AOC_DATA_DIR";
+
+// Relative to the current directory, so the binary reads the inputs of the
+// checkout it's run from rather than the one it was built in. Unit tests run
+// from wherever cargo puts them, so they use the crate's own inputs.
+#[cfg(not(test))]
+const DATA_DIR: &str = "data";
+#[cfg(test)]
+const DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/data");
+
+/// Where a day's puzzle input is read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    Default,
+    Path(PathBuf),
+    Stdin,
+}
+
+impl InputSource {
+    pub fn from_arg(value: &str) -> Self {
+        match value {
+            "-" => InputSource::Stdin,
+            path => InputSource::Path(PathBuf::from(path)),
+        }
+    }
+
+    // The CLI flag takes precedence over the environment variable, which in
+    // turn takes precedence over the day's file under `data/`.
+    pub fn resolve(arg: Option<&str>) -> Self {
+        match arg {
+            Some(value) => InputSource::from_arg(value),
+            None => match env::var(INPUT_ENV_VAR) {
+                Ok(value) if !value.is_empty() => InputSource::from_arg(&value),
+                _ => InputSource::Default,
+            },
+        }
+    }
+
+    // Name used to point at the input in parse errors.
+    pub fn name(&self, file_name: &str) -> String {
+        match self {
+            InputSource::Default => default_path(file_name).display().to_string(),
+            InputSource::Path(path) => path.display().to_string(),
+            InputSource::Stdin => "<stdin>".to_string(),
+        }
+    }
+}
+
+/// The directory holding the days' default inputs, `data/` unless
+/// overridden by the AOC_DATA_DIR environment variable.
+pub fn data_dir() -> PathBuf {
+    match env::var(DATA_DIR_ENV_VAR) {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(DATA_DIR),
+    }
+}
+
+pub fn default_path(file_name: &str) -> PathBuf {
+    data_dir().join(file_name)
+}
+
+fn read_path(path: &Path) -> AppResult<String> {
+    fs::read_to_string(path)
+        .map_err(|err| AppError::io(&format!("Failed to read input \"{}\"", path.display()), err))
+}
+
+fn read_stdin() -> AppResult<String> {
+    let mut contents = String::new();
+    io::stdin()
+        .read_to_string(&mut contents)
+        .map_err(|err| AppError::io("Failed to read input from stdin", err))?;
+
+    Ok(contents)
+}
+
+/// Reads a day's input, without any trailing newline.
+pub fn load(source: &InputSource, file_name: &str) -> AppResult<String> {
+    let contents = match source {
+        InputSource::Default => read_path(&default_path(file_name))?,
+        InputSource::Path(path) => read_path(path)?,
+        InputSource::Stdin => read_stdin()?,
+    };
+
+    // puzzle inputs are split on '\n', so a trailing newline would show up as
+    // an empty record
+    Ok(contents.trim_end_matches(&['\n', '\r'][..]).to_string())
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// Sniffs the gzip magic bytes rather than trusting the file extension, so
+// compressed input can also be piped through stdin.
+fn decompress<R: BufRead + 'static>(mut reader: R) -> io::Result<Box<dyn BufRead>> {
+    match reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        true => Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader)))),
+        false => Ok(Box::new(reader)),
+    }
+}
+
+/// Opens a day's input for reading line by line, transparently decompressing
+/// gzip'd input. Unlike [`load`] nothing is read up front, so the input can be
+/// larger than memory.
+pub fn open(source: &InputSource, file_name: &str) -> AppResult<Box<dyn BufRead>> {
+    let open_path = |path: &Path| {
+        File::open(path)
+            .and_then(|file| decompress(BufReader::new(file)))
+            .map_err(|err| {
+                AppError::io(&format!("Failed to read input \"{}\"", path.display()), err)
+            })
+    };
+
+    match source {
+        InputSource::Default => open_path(&default_path(file_name)),
+        InputSource::Path(path) => open_path(path),
+        InputSource::Stdin => decompress(BufReader::new(io::stdin()))
+            .map_err(|err| AppError::io("Failed to read input from stdin", err)),
+    }
+}
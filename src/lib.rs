@@ -0,0 +1,46 @@
+//! Solutions to the [Advent of Code 2020](https://adventofcode.com/2020)
+//! puzzles.
+//!
+//! Every day lives in its own `dNN` module and implements
+//! [`Solution`](solution::Solution), which splits a puzzle into parsing the
+//! input and solving each part. The parsers and algorithms behind each day are
+//! public as well, so they can be used on their own.
+//!
+//! ```
+//! use advent_of_code_2020::{d01::ReportRepair, solution::Solution};
+//!
+//! let report = ReportRepair.parse("1721\n979\n366\n299\n675\n1456").unwrap();
+//! assert_eq!(ReportRepair.part1(&report).unwrap(), 514579);
+//! ```
+//!
+//! [`Registry`](registry::Registry) holds every implemented day, and is what
+//! the `advent-of-code-2020` binary runs, verifies and benchmarks.
+
+#[macro_use]
+extern crate lazy_static;
+
+pub mod bench;
+pub mod cli;
+pub mod d01;
+pub mod d02;
+pub mod d03;
+pub mod d04;
+pub mod d05;
+pub mod d06;
+pub mod d07;
+pub mod d08;
+pub mod d09;
+pub mod d10;
+pub mod d11;
+pub mod d12;
+pub mod d13;
+pub mod d14;
+pub mod errors;
+pub mod grid;
+pub mod input;
+pub mod records;
+pub mod registry;
+pub mod report;
+pub mod schema;
+pub mod solution;
+pub mod verify;
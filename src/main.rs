@@ -1,57 +1,354 @@
-#[macro_use]
-extern crate lazy_static;
-
-use d01::run as run_d01;
-use d02::run as run_d02;
-use d03::run as run_d03;
-use d04::run as run_d04;
-use d05::run as run_d05;
-use d06::run as run_d06;
-use d07::run as run_d07;
-use d08::run as run_d08;
-use d09::run as run_d09;
-use d10::run as run_d10;
-use d11::run as run_d11;
-use d12::run as run_d12;
-use d13::run as run_d13;
-use d14::run as run_d14;
-use errors::AppResult;
-use std::env;
-
-mod d01;
-mod d02;
-mod d03;
-mod d04;
-mod d05;
-mod d06;
-mod d07;
-mod d08;
-mod d09;
-mod d10;
-mod d11;
-mod d12;
-mod d13;
-mod d14;
-mod errors;
-
-fn main() -> AppResult<()> {
-    let action = env::args().nth(1).expect("Must provide an argument");
-
-    match action.trim() {
-        "1" => run_d01(),
-        "2" => run_d02(),
-        "3" => run_d03(),
-        "4" => run_d04(),
-        "5" => run_d05(),
-        "6" => run_d06(),
-        "7" => run_d07(),
-        "8" => run_d08(),
-        "9" => run_d09(),
-        "10" => run_d10(),
-        "11" => run_d11(),
-        "12" => run_d12(),
-        "13" => run_d13(),
-        "14" => run_d14(),
-        _ => panic!(format!("Unknown day {}", action)),
+use advent_of_code_2020::{
+    bench,
+    cli::{self, Command, DaySelection, OutputFormat, PartSelection, PassportOptions, USAGE},
+    d01, d02, d03, d04,
+    errors::{AppError, AppResult},
+    input::{self, InputSource},
+    records::{self, RecordReader},
+    registry::Registry,
+    report::{self, Record},
+    schema::Schema,
+    verify::{self, Answers, Verdict},
+};
+use std::{
+    env,
+    error::Error,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+fn run_days(
+    registry: &Registry,
+    days: DaySelection,
+    parts: PartSelection,
+    source: InputSource,
+    format: OutputFormat,
+) -> AppResult<()> {
+    let days = registry.select(&days)?;
+
+    if days.len() > 1 && source != InputSource::Default {
+        return Err(AppError::invalid_input(
+            "A custom input can only be used when running a single day",
+        ));
+    }
+
+    let stdout = io::stdout();
+    let mut writer = stdout.lock();
+
+    if let OutputFormat::Tsv = format {
+        report::write_tsv_header(&mut writer)?;
+    }
+
+    let mut failures = 0;
+    for day in days {
+        if let OutputFormat::Text = format {
+            writeln!(writer, "Day {}", day.number)?;
+        }
+
+        let records = report::run_day(day, parts, &source);
+        if records.iter().any(Record::is_error) {
+            failures += 1;
+        }
+
+        report::write_records(&mut writer, &records, format)?;
+    }
+
+    match failures {
+        0 => Ok(()),
+        count => Err(AppError::invalid_input(&format!(
+            "{} day(s) failed to solve",
+            count
+        ))),
+    }
+}
+
+fn verify_days(registry: &Registry, days: DaySelection, answers: Option<String>) -> AppResult<()> {
+    let answers = Answers::load(
+        &answers
+            .map(PathBuf::from)
+            .unwrap_or_else(verify::default_answers_path),
+    )?;
+
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+    for day in registry.select(&days)? {
+        for (part, verdict) in verify::verify_day(io::stderr(), day, &answers)? {
+            println!("Day {} part {}: {}", day.number, part, verdict);
+
+            match verdict {
+                Verdict::Pass => passed += 1,
+                Verdict::Missing => missing += 1,
+                _ => failed += 1,
+            }
+        }
+    }
+
+    println!("{} passed, {} failed, {} missing", passed, failed, missing);
+
+    match failed {
+        0 => Ok(()),
+        count => Err(AppError::invalid_input(&format!(
+            "{} part(s) failed verification",
+            count
+        ))),
+    }
+}
+
+fn bench_days(
+    registry: &Registry,
+    days: DaySelection,
+    runs: usize,
+    format: OutputFormat,
+) -> AppResult<()> {
+    let mut measurements = vec![];
+    for day in registry.select(&days)? {
+        let input = input::load(&InputSource::Default, day.input_file)?;
+        measurements.extend(bench::bench_day(day, &input, runs));
+    }
+
+    bench::write_measurements(io::stdout().lock(), &measurements, format)
+}
+
+fn load_expense_report(registry: &Registry, source: &InputSource) -> AppResult<Vec<u32>> {
+    let day = registry
+        .get(1)
+        .ok_or_else(|| AppError::invalid_input("Day 1 is not implemented"))?;
+    let report = input::load(source, day.input_file)?;
+
+    d01::read_expense_report(report.as_bytes())
+        .map_err(|err| err.in_file(&source.name(day.input_file)))
+}
+
+fn k_sum(
+    registry: &Registry,
+    k: usize,
+    target: u32,
+    limit: Option<usize>,
+    source: InputSource,
+) -> AppResult<()> {
+    let report = load_expense_report(registry, &source)?;
+
+    d01::write_k_sums(io::stdout().lock(), &report, k, target, limit)
+}
+
+fn subset_sum(
+    registry: &Registry,
+    target: u32,
+    objective: d01::Objective,
+    source: InputSource,
+) -> AppResult<()> {
+    let report = load_expense_report(registry, &source)?;
+
+    d01::write_subset_sum(io::stdout().lock(), &report, target, objective)
+}
+
+fn check_passwords(
+    registry: &Registry,
+    policy: &str,
+    mode: d02::ParseMode,
+    audit: Option<d02::AuditFormat>,
+    progress: bool,
+    source: InputSource,
+) -> AppResult<()> {
+    let policy = d02::parse_policy(policy)?;
+
+    let day = registry
+        .get(2)
+        .ok_or_else(|| AppError::invalid_input("Day 2 is not implemented"))?;
+    let file_name = source.name(day.input_file);
+    let reader = input::open(&source, day.input_file)?;
+
+    let mut writer = io::BufWriter::new(io::stdout().lock());
+    let tally =
+        d02::check_stream(
+            reader,
+            &mut writer,
+            policy.as_ref(),
+            mode,
+            audit,
+            |event| match event {
+                d02::Progress::Skipped(err) => eprintln!("Skipped {}", err.in_file(&file_name)),
+                d02::Progress::Checked(checked) if progress => {
+                    eprintln!("Checked {} passwords", checked)
+                }
+                d02::Progress::Checked(_) => {}
+            },
+        )
+        .map_err(|err| err.in_file(&file_name))?;
+
+    if tally.skipped > 0 {
+        eprintln!("Skipped {} invalid line(s)", tally.skipped);
+    }
+
+    // the summary line would break the CSV
+    if audit != Some(d02::AuditFormat::Csv) {
+        writeln!(
+            writer,
+            "{} of {} passwords are valid",
+            tally.valid, tally.checked
+        )?;
+    }
+
+    Ok(writer.flush()?)
+}
+
+fn load_map(registry: &Registry, source: &InputSource) -> AppResult<d03::Board> {
+    let day = registry
+        .get(3)
+        .ok_or_else(|| AppError::invalid_input("Day 3 is not implemented"))?;
+    let board = input::load(source, day.input_file)?;
+
+    d03::Board::parse(&board).map_err(|err| err.in_file(&source.name(day.input_file)))
+}
+
+fn search_slopes(registry: &Registry, bound: d03::Slope, source: InputSource) -> AppResult<()> {
+    let board = load_map(registry, &source)?;
+
+    d03::write_slope_search(io::stdout().lock(), &board, &bound)
+}
+
+fn render_paths(
+    registry: &Registry,
+    slopes: &[d03::Slope],
+    ppm: bool,
+    scale: usize,
+    source: InputSource,
+) -> AppResult<()> {
+    let board = load_map(registry, &source)?;
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+
+    match ppm {
+        true => d03::render_ppm(&mut writer, &board, slopes, scale)?,
+        false => d03::render_ascii(&mut writer, &board, slopes)?,
+    }
+
+    Ok(writer.flush()?)
+}
+
+fn check_passports(
+    registry: &Registry,
+    options: &PassportOptions,
+    source: InputSource,
+) -> AppResult<()> {
+    let schema = match &options.schema {
+        Some(path) => Schema::load(Path::new(path))?,
+        None => d04::default_schema(),
+    };
+
+    let day = registry
+        .get(4)
+        .ok_or_else(|| AppError::invalid_input("Day 4 is not implemented"))?;
+    let passports = input::load(&source, day.input_file)?;
+    let passports = RecordReader::new()
+        .duplicates(options.duplicates)
+        .known_keys(&schema.keys(), options.unknown_keys)
+        .read(&passports)
+        .map_err(|err| err.in_file(&source.name(day.input_file)))?;
+
+    let mut writer = io::BufWriter::new(io::stdout().lock());
+    match (options.normalize, options.export) {
+        (true, _) => records::write_records(&mut writer, &passports)?,
+        (false, Some(format)) => d04::write_export(&mut writer, &passports, &schema, format)?,
+        (false, None) => d04::write_report(
+            &mut writer,
+            &passports,
+            &schema,
+            options.validation,
+            options.explain,
+            options.summary,
+        )?,
+    }
+
+    Ok(writer.flush()?)
+}
+
+fn main() {
+    let registry = Registry::new();
+    let result = cli::parse_args(env::args().skip(1)).and_then(|command| match command {
+        Command::Run {
+            days,
+            parts,
+            input,
+            format,
+        } => run_days(
+            &registry,
+            days,
+            parts,
+            InputSource::resolve(input.as_deref()),
+            format,
+        ),
+        Command::Verify { days, answers } => verify_days(&registry, days, answers),
+        Command::Bench { days, runs, format } => bench_days(&registry, days, runs, format),
+        Command::KSum {
+            k,
+            target,
+            limit,
+            input,
+        } => k_sum(
+            &registry,
+            k,
+            target,
+            limit,
+            InputSource::resolve(input.as_deref()),
+        ),
+        Command::SubsetSum {
+            target,
+            objective,
+            input,
+        } => subset_sum(
+            &registry,
+            target,
+            objective,
+            InputSource::resolve(input.as_deref()),
+        ),
+        Command::Passwords {
+            policy,
+            mode,
+            audit,
+            progress,
+            input,
+        } => check_passwords(
+            &registry,
+            &policy,
+            mode,
+            audit,
+            progress,
+            InputSource::resolve(input.as_deref()),
+        ),
+        Command::Slopes { bound, input } => {
+            search_slopes(&registry, bound, InputSource::resolve(input.as_deref()))
+        }
+        Command::Render {
+            slopes,
+            ppm,
+            scale,
+            input,
+        } => render_paths(
+            &registry,
+            &slopes,
+            ppm,
+            scale,
+            InputSource::resolve(input.as_deref()),
+        ),
+        Command::Passports { options, input } => {
+            check_passports(&registry, &options, InputSource::resolve(input.as_deref()))
+        }
+        Command::Help => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+    });
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+
+        let mut source = err.source();
+        while let Some(cause) = source {
+            eprintln!("  caused by: {}", cause);
+            source = cause.source();
+        }
+
+        process::exit(1);
     }
 }
@@ -0,0 +1,312 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    io::Write,
+};
+
+use crate::errors::{AppError, AppResult, ParseError};
+
+/// The `key:value` fields of a record, as written in the batch file.
+pub type Fields = HashMap<String, String>;
+
+/// The 1-based lines a record spans, inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.start == self.end {
+            true => write!(f, "line {}", self.start),
+            false => write!(f, "lines {}-{}", self.start, self.end),
+        }
+    }
+}
+
+/// A blank line separated record of a batch file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// The 1-based position of the record in the batch.
+    pub number: usize,
+    pub span: Span,
+    pub fields: Fields,
+}
+
+/// What to do with a key given more than once in a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    First,
+    Last,
+    Error,
+}
+
+/// What to do with a key outside of the known keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownKeys {
+    Keep,
+    Drop,
+    Error,
+}
+
+/// Reads batch files of records, each made of `key:value` fields separated by
+/// whitespace, and separated from the next record by blank lines.
+///
+/// Lines may end in `\n` or `\r\n`, and lines of only whitespace count as
+/// blank. Values run from the first `:` to the next whitespace, so they may
+/// contain `:` themselves.
+#[derive(Debug, Clone)]
+pub struct RecordReader {
+    duplicates: DuplicateKeys,
+    unknown: UnknownKeys,
+    known: Option<HashSet<String>>,
+}
+
+impl Default for RecordReader {
+    // the last value wins, as it did when records were collected straight
+    // into a map
+    fn default() -> Self {
+        RecordReader {
+            duplicates: DuplicateKeys::Last,
+            unknown: UnknownKeys::Keep,
+            known: None,
+        }
+    }
+}
+
+// The record being read, along with the line each of its keys was first
+// seen on.
+struct Pending {
+    span: Span,
+    fields: Fields,
+    seen: HashMap<String, usize>,
+}
+
+impl RecordReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn duplicates(mut self, policy: DuplicateKeys) -> Self {
+        self.duplicates = policy;
+        self
+    }
+
+    /// Applies `policy` to every key not in `keys`.
+    pub fn known_keys(mut self, keys: &[&str], policy: UnknownKeys) -> Self {
+        self.known = Some(keys.iter().map(|key| key.to_string()).collect());
+        self.unknown = policy;
+        self
+    }
+
+    /// Reads every record of `input`, reporting every malformed field rather
+    /// than stopping at the first.
+    pub fn read(&self, input: &str) -> AppResult<Vec<Record>> {
+        let mut records = vec![];
+        let mut errors = vec![];
+        let mut pending: Option<Pending> = None;
+
+        for (index, line) in input.lines().enumerate() {
+            let line_number = index + 1;
+
+            if line.trim().is_empty() {
+                if let Some(record) = pending.take() {
+                    records.push(Record {
+                        number: records.len() + 1,
+                        span: record.span,
+                        fields: record.fields,
+                    });
+                }
+
+                continue;
+            }
+
+            let record = pending.get_or_insert_with(|| Pending {
+                span: Span {
+                    start: line_number,
+                    end: line_number,
+                },
+                fields: Fields::new(),
+                seen: HashMap::new(),
+            });
+            record.span.end = line_number;
+
+            for token in line.split_whitespace() {
+                let error = |message: &str| ParseError::at_token(line_number, line, token, message);
+
+                let (key, value) = match token.split_once(':') {
+                    Some((key, value)) if !key.is_empty() => (key, value),
+                    _ => {
+                        errors.push(error(&format!(
+                            "Expected a key:value field, found \"{}\"",
+                            token
+                        )));
+                        continue;
+                    }
+                };
+
+                let known = self.known.as_ref().is_none_or(|keys| keys.contains(key));
+                match (known, self.unknown) {
+                    (false, UnknownKeys::Drop) => continue,
+                    (false, UnknownKeys::Error) => {
+                        errors.push(error(&format!("Unknown key \"{}\"", key)));
+                        continue;
+                    }
+                    _ => {}
+                }
+
+                match (record.seen.get(key), self.duplicates) {
+                    (None, _) => {
+                        record.seen.insert(key.to_string(), line_number);
+                    }
+                    (Some(_), DuplicateKeys::First) => continue,
+                    (Some(_), DuplicateKeys::Last) => {}
+                    (Some(first), DuplicateKeys::Error) => {
+                        errors.push(error(&format!(
+                            "Duplicate key \"{}\", first given on line {}",
+                            key, first
+                        )));
+                        continue;
+                    }
+                }
+
+                record.fields.insert(key.to_string(), value.to_string());
+            }
+        }
+
+        if let Some(record) = pending {
+            records.push(Record {
+                number: records.len() + 1,
+                span: record.span,
+                fields: record.fields,
+            });
+        }
+
+        match errors.is_empty() {
+            true => Ok(records),
+            false => Err(AppError::diagnostics(errors)),
+        }
+    }
+}
+
+/// Writes `records` back out in a normalized batch file: one line per record
+/// with its fields sorted by key, a single blank line between records, and
+/// `\n` line endings.
+pub fn write_records<W: Write>(mut writer: W, records: &[Record]) -> AppResult<()> {
+    for (index, record) in records.iter().enumerate() {
+        if index > 0 {
+            writeln!(writer)?;
+        }
+
+        let fields = record
+            .fields
+            .iter()
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .map(|(key, value)| format!("{}:{}", key, value))
+            .collect::<Vec<_>>();
+
+        writeln!(writer, "{}", fields.join(" "))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field<'a>(record: &'a Record, key: &str) -> Option<&'a str> {
+        record.fields.get(key).map(String::as_str)
+    }
+
+    #[test]
+    fn real_world_batch() {
+        let records = RecordReader::new()
+            .read("\r\n  \r\nbyr:1937 url:http://x\r\niyr:2017 \r\n \t\r\n\r\nhgt:183cm\r\n\r\n")
+            .unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].span, Span { start: 3, end: 4 });
+        assert_eq!(records[1].number, 2);
+        assert_eq!(records[1].span.to_string(), "line 7");
+        assert_eq!(field(&records[0], "url"), Some("http://x"));
+        assert_eq!(field(&records[0], "iyr"), Some("2017"));
+        assert_eq!(field(&records[1], "hgt"), Some("183cm"));
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let input = "ecl:amb\npid:1 ecl:blu";
+        let read = |policy| RecordReader::new().duplicates(policy).read(input);
+
+        assert_eq!(
+            field(&read(DuplicateKeys::First).unwrap()[0], "ecl"),
+            Some("amb")
+        );
+        assert_eq!(
+            field(&read(DuplicateKeys::Last).unwrap()[0], "ecl"),
+            Some("blu")
+        );
+        match read(DuplicateKeys::Error) {
+            Err(AppError::Parse(err)) => {
+                assert_eq!((err.line, err.column), (2, 7));
+                assert_eq!(err.message, "Duplicate key \"ecl\", first given on line 1");
+            }
+            result => panic!("Expected a parse error, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn unknown_keys() {
+        let input = "ecl:amb zzz:1\n\nfoo:2";
+        let read = |policy| RecordReader::new().known_keys(&["ecl"], policy).read(input);
+
+        assert_eq!(read(UnknownKeys::Keep).unwrap()[0].fields.len(), 2);
+        assert_eq!(read(UnknownKeys::Drop).unwrap()[1].fields.len(), 0);
+        match read(UnknownKeys::Error) {
+            Err(AppError::Diagnostics(errors)) => assert_eq!(
+                errors
+                    .iter()
+                    .map(|err| (err.line, err.column))
+                    .collect::<Vec<_>>(),
+                vec![(1, 9), (3, 1)]
+            ),
+            result => panic!("Expected diagnostics, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn malformed_fields() {
+        match RecordReader::new().read("ecl:amb\n\npid:1 oops :2") {
+            Err(AppError::Diagnostics(errors)) => assert_eq!(
+                errors
+                    .iter()
+                    .map(|err| (err.line, err.column))
+                    .collect::<Vec<_>>(),
+                vec![(3, 7), (3, 12)]
+            ),
+            result => panic!("Expected diagnostics, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn normalized_output() {
+        let records = RecordReader::new()
+            .read("pid:1 ecl:amb\r\nbyr:1937\r\n\r\n\r\n\r\nhgt:a:b\r\n")
+            .unwrap();
+        let mut output = vec![];
+
+        write_records(&mut output, &records).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!(output, "byr:1937 ecl:amb pid:1\n\nhgt:a:b\n");
+        assert!(RecordReader::new()
+            .read(&output)
+            .unwrap()
+            .iter()
+            .zip(&records)
+            .all(|(reread, record)| reread.fields == record.fields));
+    }
+}
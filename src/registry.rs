@@ -0,0 +1,80 @@
+use crate::cli::DaySelection;
+use crate::errors::{AppError, AppResult};
+use crate::solution::AnySolution;
+use crate::{d01, d02, d03, d04, d05, d06, d07, d08, d09, d10, d11, d12, d13, d14};
+
+/// A day's number, the name of its input file under `data/` and its solution.
+pub struct Day {
+    pub number: u8,
+    pub input_file: &'static str,
+    pub solution: Box<dyn AnySolution>,
+}
+
+impl Day {
+    pub fn new<S>(number: u8, input_file: &'static str, solution: S) -> Self
+    where
+        S: AnySolution + 'static,
+    {
+        Self {
+            number,
+            input_file,
+            solution: Box::new(solution),
+        }
+    }
+}
+
+/// Every implemented day, in order.
+pub struct Registry {
+    days: Vec<Day>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self {
+            days: vec![
+                Day::new(1, "expense-report.txt", d01::ReportRepair),
+                Day::new(2, "passwords.txt", d02::PasswordPhilosophy),
+                Day::new(3, "toboggan-map.txt", d03::TobogganTrajectory),
+                Day::new(4, "passports.txt", d04::PassportProcessing),
+                Day::new(5, "boarding-passes.txt", d05::BinaryBoarding),
+                Day::new(6, "customs-answers.txt", d06::CustomCustoms),
+                Day::new(7, "bag-rules.txt", d07::HandyHaversacks),
+                Day::new(8, "boot-code.txt", d08::HandheldHalting),
+                Day::new(9, "xmas-data.txt", d09::EncodingError),
+                Day::new(10, "jolts-adapters.txt", d10::AdapterArray),
+                Day::new(11, "ferry-seats.txt", d11::SeatingSystem),
+                Day::new(12, "navigation-instructions.txt", d12::RainRisk),
+                Day::new(13, "bus-notes.txt", d13::ShuttleSearch),
+                Day::new(14, "docking-program.txt", d14::DockingData),
+            ],
+        }
+    }
+
+    pub fn get(&self, number: u8) -> Option<&Day> {
+        self.days.iter().find(|day| day.number == number)
+    }
+
+    pub fn days(&self) -> impl Iterator<Item = &Day> {
+        self.days.iter()
+    }
+
+    /// Looks up every selected day, failing on days that aren't implemented.
+    pub fn select(&self, selection: &DaySelection) -> AppResult<Vec<&Day>> {
+        match selection {
+            DaySelection::All => Ok(self.days().collect()),
+            DaySelection::Days(days) => days
+                .iter()
+                .map(|&number| {
+                    self.get(number)
+                        .ok_or_else(|| AppError::invalid_input(&format!("Unknown day {}", number)))
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,276 @@
+use serde::{Serialize, Serializer};
+use std::{
+    io::{self, Write},
+    rc::Rc,
+    time::Duration,
+};
+
+use crate::bench;
+use crate::cli::{OutputFormat, Part, PartSelection};
+use crate::errors::AppError;
+use crate::errors::AppResult;
+use crate::input::{self, InputSource};
+use crate::registry::Day;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Ok,
+    Error,
+}
+
+// One record per day and part, whatever the output format, so scripts don't
+// have to care which day or part failed.
+#[derive(Debug, Serialize)]
+pub struct Record {
+    pub day: u8,
+    pub part: Part,
+    pub status: Status,
+    pub answer: Option<String>,
+    pub parse_ns: Option<u64>,
+    pub solve_ns: Option<u64>,
+    // shared by every part when the input fails to parse, keeping the kind,
+    // position and source of the original error
+    #[serde(serialize_with = "serialize_error")]
+    pub error: Option<Rc<AppError>>,
+}
+
+fn serialize_error<S: Serializer>(
+    error: &Option<Rc<AppError>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match error {
+        Some(err) => serializer.collect_str(err),
+        None => serializer.serialize_none(),
+    }
+}
+
+impl Record {
+    pub fn new(
+        day: u8,
+        part: Part,
+        parse_time: Option<Duration>,
+        result: Result<(String, Duration), Rc<AppError>>,
+    ) -> Self {
+        let parse_ns = parse_time.map(|elapsed| elapsed.as_nanos() as u64);
+
+        match result {
+            Ok((answer, elapsed)) => Self {
+                day,
+                part,
+                status: Status::Ok,
+                answer: Some(answer),
+                parse_ns,
+                solve_ns: Some(elapsed.as_nanos() as u64),
+                error: None,
+            },
+            Err(err) => Self {
+                day,
+                part,
+                status: Status::Error,
+                answer: None,
+                parse_ns,
+                solve_ns: None,
+                error: Some(err),
+            },
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.status == Status::Error
+    }
+}
+
+fn write_text<W: Write>(mut writer: W, records: &[Record]) -> AppResult<()> {
+    for record in records {
+        match (&record.answer, &record.error) {
+            (Some(answer), _) => writeln!(
+                writer,
+                "Part {}: \"{}\" ({:.2?})",
+                record.part,
+                answer,
+                Duration::from_nanos(record.solve_ns.unwrap_or_default())
+            )?,
+            (None, Some(err)) => writeln!(writer, "Part {}: {}", record.part, err)?,
+            (None, None) => (),
+        }
+    }
+
+    Ok(())
+}
+
+// tabs and newlines would break the columns, so they're flattened to spaces
+fn tsv_field<T: ToString>(value: &Option<T>) -> String {
+    match value {
+        Some(value) => value.to_string().replace(&['\t', '\n', '\r'][..], " "),
+        None => String::new(),
+    }
+}
+
+// quotes fields that would otherwise break the row, doubling any quotes
+pub(crate) fn csv_field(value: &str) -> String {
+    match value.contains(&[',', '"', '\n', '\r'][..]) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_string(),
+    }
+}
+
+/// Runs the selected parts of `day` against the input from `source`.
+///
+/// Failures are recorded per part instead of stopping the run, so a day
+/// that fails to parse still produces a record for each part.
+pub fn run_day(day: &Day, parts: PartSelection, source: &InputSource) -> Vec<Record> {
+    let input = input::load(source, day.input_file);
+
+    solve_input(day, parts, input, &source.name(day.input_file))
+}
+
+fn solve_input(
+    day: &Day,
+    parts: PartSelection,
+    input: AppResult<String>,
+    file: &str,
+) -> Vec<Record> {
+    let parts = [Part::One, Part::Two]
+        .iter()
+        .copied()
+        .filter(|&part| parts.includes(part))
+        .collect::<Vec<_>>();
+
+    let (parsed, parse_time) = match input {
+        Ok(input) => bench::time(|| day.solution.parse(&input)),
+        Err(err) => (Err(err), Default::default()),
+    };
+
+    let parsed = match parsed {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            let err = Rc::new(err.in_file(file));
+
+            return parts
+                .into_iter()
+                .map(|part| Record::new(day.number, part, None, Err(Rc::clone(&err))))
+                .collect();
+        }
+    };
+
+    parts
+        .into_iter()
+        .map(|part| {
+            let (answer, elapsed) = bench::time(|| parsed.solve(part));
+
+            Record::new(
+                day.number,
+                part,
+                Some(parse_time),
+                answer.map(|answer| (answer, elapsed)).map_err(Rc::new),
+            )
+        })
+        .collect()
+}
+
+pub fn write_tsv_header<W: Write>(mut writer: W) -> AppResult<()> {
+    writeln!(
+        writer,
+        "day\tpart\tstatus\tanswer\tparse_ns\tsolve_ns\terror"
+    )?;
+
+    Ok(())
+}
+
+fn write_tsv<W: Write>(mut writer: W, records: &[Record]) -> AppResult<()> {
+    for record in records {
+        writeln!(
+            writer,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            record.day,
+            record.part,
+            match record.status {
+                Status::Ok => "ok",
+                Status::Error => "error",
+            },
+            tsv_field(&record.answer),
+            tsv_field(&record.parse_ns),
+            tsv_field(&record.solve_ns),
+            tsv_field(&record.error)
+        )?;
+    }
+
+    Ok(())
+}
+
+// JSON lines, one object per record, so the output can be consumed as it's
+// produced
+fn write_json<W: Write>(mut writer: W, records: &[Record]) -> AppResult<()> {
+    for record in records {
+        serde_json::to_writer(&mut writer, record).map_err(io::Error::from)?;
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the records of a day in `format`. Failed parts are written along
+/// with the answers, so scripts only have to read a single stream.
+pub fn write_records<W: Write>(
+    writer: W,
+    records: &[Record],
+    format: OutputFormat,
+) -> AppResult<()> {
+    match format {
+        OutputFormat::Text => write_text(writer, records),
+        OutputFormat::Tsv => write_tsv(writer, records),
+        OutputFormat::Json => write_json(writer, records),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::d01;
+
+    #[test]
+    fn parse_errors_are_kept() {
+        let day = Day::new(1, "expense-report.txt", d01::ReportRepair);
+        let records = solve_input(
+            &day,
+            PartSelection::Both,
+            Ok("1721\nabc".to_string()),
+            "data/expense-report.txt",
+        );
+
+        assert_eq!(records.len(), 2);
+        for record in records {
+            match record.error.as_deref() {
+                Some(AppError::Parse(err)) => {
+                    assert_eq!(err.file.as_deref(), Some("data/expense-report.txt"));
+                    assert_eq!((err.line, err.column), (2, 1));
+                }
+                err => panic!("Expected a parse error, got {:?}", err),
+            }
+        }
+    }
+
+    #[test]
+    fn written_records() {
+        let day = Day::new(1, "expense-report.txt", d01::ReportRepair);
+        let records = solve_input(
+            &day,
+            PartSelection::Only(Part::One),
+            Ok("1721\n979\n366\n299\n675\n1456".to_string()),
+            "data/expense-report.txt",
+        );
+        let write = |format| {
+            let mut output = vec![];
+            write_records(&mut output, &records, format).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        assert!(write(OutputFormat::Text).starts_with("Part 1: \"514579\" ("));
+        assert!(write(OutputFormat::Tsv).starts_with("1\t1\tok\t514579\t"));
+
+        let json = serde_json::from_str::<serde_json::Value>(&write(OutputFormat::Json)).unwrap();
+        assert_eq!(json["answer"], "514579");
+        assert_eq!(json["error"], serde_json::Value::Null);
+    }
+}
@@ -0,0 +1,518 @@
+use regex::Regex;
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt, fs,
+    path::Path,
+};
+
+use crate::errors::{AppError, AppResult};
+
+/// An inclusive range of whole numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Range {
+    pub min: u64,
+    pub max: u64,
+}
+
+fn is_number(value: &str) -> bool {
+    // only plain digits, so `+42` or ` 42` don't sneak through `u64::from_str`
+    !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+impl Range {
+    // Checks the number at the start of `value`, which may be followed by a
+    // unit.
+    fn violation(&self, value: &str, number: &str) -> Option<String> {
+        if !is_number(number) {
+            return Some(format!("\"{}\" is not a number", value));
+        }
+
+        match number.parse::<u64>() {
+            Ok(number) if number >= self.min && number <= self.max => None,
+            _ => Some(format!("{} outside {}", value, self)),
+        }
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}..={}", self.min, self.max)
+    }
+}
+
+/// The kinds of rule a field can break.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RuleKind {
+    Missing,
+    Range,
+    Units,
+    Digits,
+    OneOf,
+    Pattern,
+}
+
+impl fmt::Display for RuleKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            RuleKind::Missing => "missing",
+            RuleKind::Range => "range",
+            RuleKind::Units => "units",
+            RuleKind::Digits => "digits",
+            RuleKind::OneOf => "one-of",
+            RuleKind::Pattern => "pattern",
+        })
+    }
+}
+
+/// A rule broken by a field of a record, e.g. `hgt: 190in outside 59..=76`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub key: String,
+    pub rule: RuleKind,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.message)
+    }
+}
+
+/// How a single field is validated, as written in a schema file or built with
+/// [`SchemaBuilder`]. Every rule given has to hold.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct FieldSpec {
+    pub key: String,
+    #[serde(default = "FieldSpec::default_required")]
+    pub required: bool,
+    /// The value is a number within the range.
+    pub range: Option<Range>,
+    /// The value is a number followed by one of the units, within the range
+    /// of that unit, e.g. `170cm`.
+    pub units: Option<BTreeMap<String, Range>>,
+    /// The value is exactly this many digits.
+    pub digits: Option<usize>,
+    /// The value is one of the listed values.
+    pub one_of: Option<Vec<String>>,
+    /// The whole value matches the regular expression.
+    pub pattern: Option<String>,
+}
+
+impl FieldSpec {
+    fn default_required() -> bool {
+        true
+    }
+
+    pub fn required(key: &str) -> Self {
+        FieldSpec {
+            key: key.to_string(),
+            required: true,
+            ..FieldSpec::default()
+        }
+    }
+
+    pub fn optional(key: &str) -> Self {
+        FieldSpec {
+            key: key.to_string(),
+            required: false,
+            ..FieldSpec::default()
+        }
+    }
+
+    pub fn range(mut self, min: u64, max: u64) -> Self {
+        self.range = Some(Range { min, max });
+        self
+    }
+
+    pub fn unit(mut self, unit: &str, min: u64, max: u64) -> Self {
+        self.units
+            .get_or_insert_with(BTreeMap::new)
+            .insert(unit.to_string(), Range { min, max });
+        self
+    }
+
+    pub fn digits(mut self, digits: usize) -> Self {
+        self.digits = Some(digits);
+        self
+    }
+
+    pub fn one_of(mut self, values: &[&str]) -> Self {
+        self.one_of = Some(values.iter().map(|value| value.to_string()).collect());
+        self
+    }
+
+    pub fn pattern(mut self, pattern: &str) -> Self {
+        self.pattern = Some(pattern.to_string());
+        self
+    }
+}
+
+// Splits `value` into its number and the longest of `units` it ends in, so
+// `5min` is minutes even when `in` is a unit too, along with that unit's
+// range.
+fn split_unit<'a, 'b>(
+    units: &'b BTreeMap<String, Range>,
+    value: &'a str,
+) -> Option<(&'a str, &'a str, &'b Range)> {
+    units
+        .iter()
+        .filter(|(unit, _)| value.ends_with(unit.as_str()))
+        .max_by_key(|(unit, _)| unit.len())
+        .map(|(unit, range)| {
+            let (number, unit) = value.split_at(value.len() - unit.len());
+            (number, unit, range)
+        })
+}
+
+// A rule compiled from a `FieldSpec`, ready to check values.
+#[derive(Debug)]
+enum Check {
+    Range(Range),
+    Units(BTreeMap<String, Range>),
+    Digits(usize),
+    OneOf(Vec<String>),
+    Pattern(Regex),
+}
+
+impl Check {
+    fn kind(&self) -> RuleKind {
+        match self {
+            Check::Range(_) => RuleKind::Range,
+            Check::Units(_) => RuleKind::Units,
+            Check::Digits(_) => RuleKind::Digits,
+            Check::OneOf(_) => RuleKind::OneOf,
+            Check::Pattern(_) => RuleKind::Pattern,
+        }
+    }
+
+    // Why `value` breaks the rule, if it does.
+    fn violation(&self, value: &str) -> Option<String> {
+        match self {
+            Check::Range(range) => range.violation(value, value),
+            Check::Units(units) => match split_unit(units, value) {
+                Some((number, _, range)) => range.violation(value, number),
+                None => Some(format!(
+                    "\"{}\" has no unit, expected {}",
+                    value,
+                    units.keys().cloned().collect::<Vec<_>>().join(" or ")
+                )),
+            },
+            Check::Digits(digits) => match is_number(value) {
+                true if value.len() == *digits => None,
+                true => Some(format!("{} digits, expected {}", value.len(), digits)),
+                false => Some(format!("\"{}\" is not a number", value)),
+            },
+            Check::OneOf(values) => match values.iter().any(|allowed| allowed == value) {
+                true => None,
+                false => Some(format!("\"{}\" is not one of {}", value, values.join(", "))),
+            },
+            Check::Pattern(regex) => match regex.is_match(value) {
+                true => None,
+                // the anchors added when compiling aren't part of the schema
+                false => Some(format!(
+                    "\"{}\" does not match {}",
+                    value,
+                    &regex.as_str()[4..regex.as_str().len() - 2]
+                )),
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Field {
+    key: String,
+    required: bool,
+    checks: Vec<Check>,
+}
+
+impl Field {
+    fn compile(spec: FieldSpec) -> AppResult<Self> {
+        let FieldSpec {
+            key,
+            required,
+            range,
+            units,
+            digits,
+            one_of,
+            pattern,
+        } = spec;
+        let mut checks = vec![];
+
+        if let Some(range) = range {
+            checks.push(Check::Range(range));
+        }
+
+        if let Some(units) = units {
+            if units.is_empty() {
+                return Err(AppError::invalid_input(&format!(
+                    "Field \"{}\" must allow at least 1 unit",
+                    key
+                )));
+            }
+
+            checks.push(Check::Units(units));
+        }
+
+        if let Some(digits) = digits {
+            checks.push(Check::Digits(digits));
+        }
+
+        if let Some(values) = one_of {
+            checks.push(Check::OneOf(values));
+        }
+
+        if let Some(pattern) = pattern {
+            // patterns describe the whole value, not just part of it
+            let regex = Regex::new(&format!("^(?:{})$", pattern)).map_err(|err| {
+                AppError::invalid_input(&format!("Invalid pattern for field \"{}\": {}", key, err))
+            })?;
+
+            checks.push(Check::Pattern(regex));
+        }
+
+        Ok(Field {
+            key,
+            required,
+            checks,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SchemaFile {
+    fields: Vec<FieldSpec>,
+}
+
+/// Collects the fields of a [`Schema`].
+#[derive(Debug, Default)]
+pub struct SchemaBuilder {
+    fields: Vec<FieldSpec>,
+}
+
+impl SchemaBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field(mut self, field: FieldSpec) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// Compiles the validators of every field, failing on invalid patterns or
+    /// fields given twice.
+    pub fn build(self) -> AppResult<Schema> {
+        let mut keys = HashSet::new();
+        for field in &self.fields {
+            if !keys.insert(field.key.as_str()) {
+                return Err(AppError::invalid_input(&format!(
+                    "Field \"{}\" is given more than once",
+                    field.key
+                )));
+            }
+        }
+
+        Ok(Schema {
+            fields: self
+                .fields
+                .into_iter()
+                .map(Field::compile)
+                .collect::<AppResult<_>>()?,
+        })
+    }
+}
+
+/// Rules for the fields of a record, such as a passport.
+///
+/// Schemas are built in code with [`SchemaBuilder`], or loaded from a TOML or
+/// JSON file listing the same [`FieldSpec`]s:
+///
+/// ```toml
+/// [[fields]]
+/// key = "hgt"
+/// units = { cm = { min = 150, max = 193 }, in = { min = 59, max = 76 } }
+///
+/// [[fields]]
+/// key = "cid"
+/// required = false
+/// ```
+#[derive(Debug)]
+pub struct Schema {
+    fields: Vec<Field>,
+}
+
+impl Schema {
+    pub fn builder() -> SchemaBuilder {
+        SchemaBuilder::new()
+    }
+
+    fn from_file(file: SchemaFile) -> AppResult<Self> {
+        file.fields
+            .into_iter()
+            .fold(SchemaBuilder::new(), SchemaBuilder::field)
+            .build()
+    }
+
+    pub fn from_toml(contents: &str) -> AppResult<Self> {
+        let file = toml::from_str(contents)
+            .map_err(|err| AppError::invalid_input(&format!("Invalid schema: {}", err)))?;
+
+        Self::from_file(file)
+    }
+
+    pub fn from_json(contents: &str) -> AppResult<Self> {
+        let file = serde_json::from_str(contents)
+            .map_err(|err| AppError::invalid_input(&format!("Invalid schema: {}", err)))?;
+
+        Self::from_file(file)
+    }
+
+    /// Loads a schema file, read as JSON when it ends in `.json` and as TOML
+    /// otherwise.
+    pub fn load(path: &Path) -> AppResult<Self> {
+        let contents = fs::read_to_string(path).map_err(|err| {
+            AppError::io(
+                &format!("Failed to read schema \"{}\"", path.display()),
+                err,
+            )
+        })?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Self::from_json(&contents),
+            _ => Self::from_toml(&contents),
+        }
+    }
+
+    /// Every rule `fields` breaks, in the order of the schema. Required
+    /// fields that are missing break the `missing` rule.
+    pub fn validate(&self, fields: &HashMap<String, String>) -> Vec<Violation> {
+        self.fields
+            .iter()
+            .flat_map(|field| {
+                let violation = |rule, message| Violation {
+                    key: field.key.clone(),
+                    rule,
+                    message,
+                };
+
+                match fields.get(&field.key) {
+                    Some(value) => field
+                        .checks
+                        .iter()
+                        .filter_map(|check| Some(violation(check.kind(), check.violation(value)?)))
+                        .collect(),
+                    None if field.required => {
+                        vec![violation(RuleKind::Missing, "missing".to_string())]
+                    }
+                    None => vec![],
+                }
+            })
+            .collect()
+    }
+
+    /// Splits `value` of the field `key` into its number and unit, taking the
+    /// longest of the field's units it ends in. `None` when the field has no
+    /// units, or `value` ends in none of them.
+    pub fn split_unit<'a>(&self, key: &str, value: &'a str) -> Option<(&'a str, &'a str)> {
+        self.fields
+            .iter()
+            .filter(|field| field.key == key)
+            .flat_map(|field| &field.checks)
+            .find_map(|check| match check {
+                Check::Units(units) => split_unit(units, value),
+                _ => None,
+            })
+            .map(|(number, unit, _)| (number, unit))
+    }
+
+    /// The keys of every field, in the order of the schema.
+    pub fn keys(&self) -> Vec<&str> {
+        self.fields.iter().map(|field| field.key.as_str()).collect()
+    }
+
+    /// The required fields missing from `fields`, without looking at the
+    /// values of the fields present.
+    pub fn missing(&self, fields: &HashMap<String, String>) -> Vec<Violation> {
+        self.fields
+            .iter()
+            .filter(|field| field.required && !fields.contains_key(&field.key))
+            .map(|field| Violation {
+                key: field.key.clone(),
+                rule: RuleKind::Missing,
+                message: "missing".to_string(),
+            })
+            .collect()
+    }
+
+    /// Whether every required field is present, whatever its value.
+    pub fn has_required(&self, fields: &HashMap<String, String>) -> bool {
+        self.fields
+            .iter()
+            .all(|field| !field.required || fields.contains_key(&field.key))
+    }
+
+    /// Whether every required field is present, and every field present
+    /// passes its rules.
+    pub fn is_valid(&self, fields: &HashMap<String, String>) -> bool {
+        self.fields
+            .iter()
+            .all(|field| match fields.get(&field.key) {
+                Some(value) => field
+                    .checks
+                    .iter()
+                    .all(|check| check.violation(value).is_none()),
+                None => !field.required,
+            })
+    }
+}
+
+/// How many records a rule of a field rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleCount {
+    pub key: String,
+    pub rule: RuleKind,
+    pub records: usize,
+}
+
+/// Tallies the records rejected by each rule, given the violations of every
+/// record, most rejections first.
+pub fn count_rejections<'a, I>(records: I) -> Vec<RuleCount>
+where
+    I: IntoIterator<Item = &'a [Violation]>,
+{
+    let mut counts: HashMap<(&str, RuleKind), usize> = HashMap::new();
+
+    for violations in records {
+        // a rule can only reject a record once
+        let rules = violations
+            .iter()
+            .map(|violation| (violation.key.as_str(), violation.rule))
+            .collect::<HashSet<_>>();
+
+        for rule in rules {
+            *counts.entry(rule).or_default() += 1;
+        }
+    }
+
+    let mut counts = counts
+        .into_iter()
+        .map(|((key, rule), records)| RuleCount {
+            key: key.to_string(),
+            rule,
+            records,
+        })
+        .collect::<Vec<_>>();
+
+    counts.sort_by(|a, b| {
+        b.records
+            .cmp(&a.records)
+            .then_with(|| (&a.key, a.rule).cmp(&(&b.key, b.rule)))
+    });
+
+    counts
+}
@@ -0,0 +1,55 @@
+use std::fmt::Display;
+
+use crate::cli::Part;
+use crate::errors::AppResult;
+
+/// A day's puzzle. The input is parsed once and shared by both parts.
+pub trait Solution {
+    type Input;
+    type Answer: Display;
+
+    /// Parses the puzzle input, without any trailing newline.
+    fn parse(&self, input: &str) -> AppResult<Self::Input>;
+
+    fn part1(&self, input: &Self::Input) -> AppResult<Self::Answer>;
+
+    fn part2(&self, input: &Self::Input) -> AppResult<Self::Answer>;
+}
+
+/// Object safe view of a [`Solution`] so days with different input and answer
+/// types can live side by side in the registry.
+pub trait AnySolution {
+    fn parse<'a>(&'a self, input: &str) -> AppResult<Box<dyn ParsedInput + 'a>>;
+}
+
+/// A parsed input along with the solution that parsed it.
+pub trait ParsedInput {
+    fn solve(&self, part: Part) -> AppResult<String>;
+}
+
+struct Parsed<'a, S: Solution> {
+    solution: &'a S,
+    input: S::Input,
+}
+
+impl<'a, S: Solution> ParsedInput for Parsed<'a, S> {
+    fn solve(&self, part: Part) -> AppResult<String> {
+        let answer = match part {
+            Part::One => self.solution.part1(&self.input)?,
+            Part::Two => self.solution.part2(&self.input)?,
+        };
+
+        Ok(answer.to_string())
+    }
+}
+
+impl<S: Solution> AnySolution for S {
+    fn parse<'a>(&'a self, input: &str) -> AppResult<Box<dyn ParsedInput + 'a>> {
+        let input = Solution::parse(self, input)?;
+
+        Ok(Box::new(Parsed {
+            solution: self,
+            input,
+        }))
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    collections::HashMap,
+    env, fmt, fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use toml::Value;
+
+use crate::cli::Part;
+use crate::errors::{AppError, AppResult};
+use crate::input::{self, InputSource};
+use crate::registry::Day;
+
+pub const ANSWERS_ENV_VAR: &str = "AOC_ANSWERS";
+
+// Resolved against the current directory like the inputs under `data/`.
+#[cfg(not(test))]
+const DEFAULT_ANSWERS_PATH: &str = "answers.toml";
+#[cfg(test)]
+const DEFAULT_ANSWERS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/answers.toml");
+
+/// The answers file used when none is given, `answers.toml` unless
+/// overridden by the AOC_ANSWERS environment variable.
+pub fn default_answers_path() -> PathBuf {
+    match env::var(ANSWERS_ENV_VAR) {
+        Ok(path) if !path.is_empty() => PathBuf::from(path),
+        _ => PathBuf::from(DEFAULT_ANSWERS_PATH),
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Answers {
+    expected: HashMap<(u8, Part), String>,
+}
+
+impl Answers {
+    pub fn parse(contents: &str) -> AppResult<Self> {
+        let table = match contents.parse::<Value>() {
+            Ok(Value::Table(table)) => table,
+            Ok(_) => return Err(AppError::invalid_input("Answers file must be a table")),
+            Err(err) => {
+                return Err(AppError::invalid_input(&format!(
+                    "Invalid answers file: {}",
+                    err
+                )))
+            }
+        };
+
+        let mut expected = HashMap::new();
+        for (key, parts) in table {
+            let day = key
+                .strip_prefix("day")
+                .and_then(|day| day.parse::<u8>().ok())
+                .ok_or_else(|| {
+                    AppError::invalid_input(&format!("Unknown answers section [{}]", key))
+                })?;
+
+            let parts = parts
+                .as_table()
+                .ok_or_else(|| AppError::invalid_input(&format!("[{}] must be a table", key)))?;
+
+            for (name, value) in parts {
+                let part = match name.as_str() {
+                    "part1" => Part::One,
+                    "part2" => Part::Two,
+                    name => {
+                        return Err(AppError::invalid_input(&format!(
+                            "Unknown part \"{}\" in [{}]",
+                            name, key
+                        )))
+                    }
+                };
+
+                let value = match value {
+                    Value::String(value) => value.clone(),
+                    Value::Integer(value) => value.to_string(),
+                    value => {
+                        return Err(AppError::invalid_input(&format!(
+                            "Unsupported answer {} for {}.{}",
+                            value, key, name
+                        )))
+                    }
+                };
+
+                expected.insert((day, part), value);
+            }
+        }
+
+        Ok(Self { expected })
+    }
+
+    pub fn load(path: &Path) -> AppResult<Self> {
+        let contents = fs::read_to_string(path).map_err(|err| {
+            AppError::invalid_input(&format!(
+                "Failed to read answers \"{}\": {}",
+                path.display(),
+                err
+            ))
+        })?;
+
+        Self::parse(&contents)
+    }
+
+    pub fn get(&self, day: u8, part: Part) -> Option<&str> {
+        self.expected.get(&(day, part)).map(String::as_str)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: String, actual: String },
+    Error(String),
+    Missing,
+}
+
+impl Verdict {
+    pub fn check(expected: Option<&str>, actual: AppResult<String>) -> Self {
+        match (expected, actual) {
+            (None, _) => Verdict::Missing,
+            (Some(expected), Ok(actual)) if expected == actual => Verdict::Pass,
+            (Some(expected), Ok(actual)) => Verdict::Fail {
+                expected: expected.to_string(),
+                actual,
+            },
+            (Some(_), Err(err)) => Verdict::Error(err.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Fail { expected, actual } => {
+                write!(f, "FAIL expected \"{}\", got \"{}\"", expected, actual)
+            }
+            Verdict::Error(err) => write!(f, "FAIL {}", err),
+            Verdict::Missing => write!(f, "missing"),
+        }
+    }
+}
+
+/// Solves both parts of `day` with its default input and checks them against
+/// `answers`. An input that fails to parse is reported to `diagnostics` and
+/// fails both parts.
+pub fn verify_day<W: Write>(
+    mut diagnostics: W,
+    day: &Day,
+    answers: &Answers,
+) -> AppResult<Vec<(Part, Verdict)>> {
+    let parsed = match input::load(&InputSource::Default, day.input_file)
+        .and_then(|input| day.solution.parse(&input))
+    {
+        Ok(parsed) => Some(parsed),
+        Err(err) => {
+            let err = err.in_file(&InputSource::Default.name(day.input_file));
+            writeln!(diagnostics, "Day {}: {}", day.number, err)?;
+            None
+        }
+    };
+
+    Ok([Part::One, Part::Two]
+        .iter()
+        .map(|&part| {
+            let actual = match &parsed {
+                Some(parsed) => parsed.solve(part),
+                None => Err(AppError::invalid_input("Failed to parse input")),
+            };
+
+            (part, Verdict::check(answers.get(day.number, part), actual))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_answers() {
+        let answers =
+            Answers::parse("[day01]\npart1 = 514579\n\n[day13]\npart2 = \"1068781\"").unwrap();
+
+        assert_eq!(answers.get(1, Part::One), Some("514579"));
+        assert_eq!(answers.get(1, Part::Two), None);
+        assert_eq!(answers.get(13, Part::Two), Some("1068781"));
+    }
+
+    #[test]
+    fn invalid_answers() {
+        for contents in [
+            "[day01",
+            "[dayone]\npart1 = 1",
+            "[day01]\npart3 = 1",
+            "[day01]\npart1 = 1.5",
+            "day01 = 1",
+        ]
+        .iter()
+        {
+            assert!(Answers::parse(contents).is_err(), "{}", contents);
+        }
+    }
+}
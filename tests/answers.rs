@@ -0,0 +1,101 @@
+use flate2::{write::GzEncoder, Compression};
+use std::process::{Command, Stdio};
+use std::{env, io::Write};
+
+// Runs every day against the committed inputs under `data/` and checks the
+// answers recorded in `answers.toml`.
+#[test]
+fn known_answers() {
+    let output = Command::new(env!("CARGO_BIN_EXE_advent-of-code-2020"))
+        .arg("verify")
+        .output()
+        .expect("Failed to run verify");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success(), "{}{}", stdout, stderr);
+    assert!(!stdout.contains("FAIL"), "{}", stdout);
+
+    for day in 1..=14 {
+        assert!(
+            stdout.contains(&format!("Day {} part 2: pass", day)),
+            "Day {} was not verified\n{}",
+            day,
+            stdout
+        );
+    }
+}
+
+#[test]
+fn json_records() {
+    let output = Command::new(env!("CARGO_BIN_EXE_advent-of-code-2020"))
+        .args(["run", "1..=3", "--format", "json"])
+        .output()
+        .expect("Failed to run days");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let records = stdout
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+
+    assert!(output.status.success());
+    assert_eq!(records.len(), 6);
+    assert_eq!(records[0]["day"], 1);
+    assert_eq!(records[0]["part"], 1);
+    assert_eq!(records[0]["status"], "ok");
+    assert_eq!(records[0]["answer"], "158916");
+    assert_eq!(records[5]["answer"], "6708199680");
+}
+
+#[test]
+fn gzipped_passwords_from_stdin() {
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder
+        .write_all(b"1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n")
+        .unwrap();
+    let input = encoder.finish().unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_advent-of-code-2020"))
+        .args(["passwords", "--input", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to check passwords");
+    child.stdin.take().unwrap().write_all(&input).unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "2 of 3 passwords are valid\n"
+    );
+}
+
+// The default inputs are looked up from the current directory, so running
+// from elsewhere needs AOC_DATA_DIR to find them.
+#[test]
+fn data_dir_from_the_environment() {
+    let run = |data_dir: Option<&str>| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_advent-of-code-2020"));
+        command
+            .args(["run", "1", "--format", "tsv"])
+            .current_dir(env::temp_dir())
+            .env_remove("AOC_INPUT")
+            .env_remove("AOC_DATA_DIR");
+        if let Some(data_dir) = data_dir {
+            command.env("AOC_DATA_DIR", data_dir);
+        }
+
+        command.output().expect("Failed to run day 1")
+    };
+
+    let output = run(None);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Failed to read input"));
+
+    let output = run(Some(concat!(env!("CARGO_MANIFEST_DIR"), "/data")));
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("1\t1\tok\t158916\t"));
+}